@@ -0,0 +1,172 @@
+//! Compares `pack_collection` with painting every sprite into the atlas under a single lock,
+//! as it used to, on a generated collection.
+//!
+//! Run with `cargo bench --bench pack`.
+
+use std::{
+    fs,
+    path::Path,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use image::{DynamicImage, GenericImage, GenericImageView, Rgba, RgbaImage};
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use spritepacker::{
+    packer::pack::{atlas_position, pack_collection},
+    tk2d::{
+        cln::Collection,
+        sprite::{Sprite, SpriteImage},
+    },
+    Result,
+};
+
+const ATLAS_SIZE: u32 = 2048;
+const FRAME_SIZE: u32 = 64;
+const RUNS: u32 = 5;
+
+fn main() {
+    let sprites_path = std::env::temp_dir().join("spritepacker-bench");
+    let collection = generate_collection(&sprites_path);
+    println!(
+        "Packing {} sprites into a {}x{} atlas, best of {} runs",
+        collection.sprites.len(),
+        ATLAS_SIZE,
+        ATLAS_SIZE,
+        RUNS
+    );
+
+    let locked_time = best_time(|| {
+        pack_collection_locked(&collection, &sprites_path).expect("Failed to pack collection");
+    });
+    println!("Single lock: {:?}", locked_time);
+    let patched_time = best_time(|| {
+        pack_collection(&collection, &sprites_path, None, |_| {})
+            .expect("Failed to pack collection");
+    });
+    println!("Patches:     {:?}", patched_time);
+    println!(
+        "Speedup:     {:.2}x",
+        locked_time.as_secs_f64() / patched_time.as_secs_f64()
+    );
+
+    let locked_atlas =
+        pack_collection_locked(&collection, &sprites_path).expect("Failed to pack collection");
+    let patched_atlas = pack_collection(&collection, &sprites_path, None, |_| {})
+        .expect("Failed to pack collection");
+    assert_eq!(
+        locked_atlas.to_rgba8().as_raw(),
+        patched_atlas.to_rgba8().as_raw(),
+        "The atlases differ"
+    );
+
+    let _ = fs::remove_dir_all(&sprites_path);
+}
+
+/// Time a closure several times.
+/// # Arguments
+/// * `run` - The closure to time
+/// # Returns
+/// * `Duration` The fastest run
+fn best_time(run: impl Fn()) -> Duration {
+    (0..RUNS)
+        .map(|_| {
+            let start = Instant::now();
+            run();
+            start.elapsed()
+        })
+        .min()
+        .unwrap_or_default()
+}
+
+/// Write a collection that fills an atlas with frames, every other one of them flipped.
+/// # Arguments
+/// * `sprites_path` - The folder to write the frames and the atlas to
+/// # Returns
+/// * `Collection` The generated collection
+fn generate_collection(sprites_path: &Path) -> Collection {
+    let frames_path = sprites_path.join("Bench");
+    fs::create_dir_all(&frames_path).expect("Failed to create bench folder");
+    let atlas_path = sprites_path.join("Bench.png");
+    RgbaImage::new(ATLAS_SIZE, ATLAS_SIZE)
+        .save(&atlas_path)
+        .expect("Failed to save atlas");
+
+    let per_row = ATLAS_SIZE / FRAME_SIZE;
+    let mut sprites = vec![];
+    for id in 0..per_row * per_row {
+        let frame_image = RgbaImage::from_fn(FRAME_SIZE, FRAME_SIZE, |x, y| {
+            Rgba([(x * 4) as u8, (y * 4) as u8, id as u8, 255])
+        });
+        let name = format!("Bench-{}-{}.png", id / per_row, id);
+        frame_image
+            .save(frames_path.join(&name))
+            .expect("Failed to save frame");
+        sprites.push(Sprite {
+            id,
+            x: ((id % per_row) * FRAME_SIZE) as i32,
+            y: ((id / per_row) * FRAME_SIZE) as i32,
+            xr: 2,
+            yr: 2,
+            width: (FRAME_SIZE - 4) as i32,
+            height: (FRAME_SIZE - 4) as i32,
+            collection_name: "Bench".to_string(),
+            name: name.clone(),
+            path: format!("Bench/{}", name),
+            flipped: id % 2 == 1,
+        });
+    }
+
+    Collection {
+        name: "Bench".to_string(),
+        path: atlas_path,
+        sprites,
+    }
+}
+
+/// Pack a collection the way `pack_collection` used to, painting each sprite while holding a lock on the whole atlas.
+/// # Arguments
+/// * `collection` - The collection to pack
+/// * `sprites_path` - The path to the sprites folder
+/// # Returns
+/// * `Result<DynamicImage>` The generated atlas
+fn pack_collection_locked(collection: &Collection, sprites_path: &Path) -> Result<DynamicImage> {
+    let atlas = image::open(&collection.path).expect("Failed to open atlas");
+    let gen_atlas = Mutex::new(atlas);
+    collection
+        .sprites
+        .par_iter()
+        .try_for_each(|sprite| -> Result<()> {
+            let frame_image = SpriteImage::open(sprites_path, sprite)?;
+            blit_sprite(&mut gen_atlas.lock().unwrap(), sprite, &frame_image.image);
+            Ok(())
+        })?;
+
+    Ok(gen_atlas.into_inner().unwrap())
+}
+
+/// Copy the trimmed region of a frame into its place in an atlas.
+/// # Arguments
+/// * `atlas` - The atlas to copy into
+/// * `sprite` - The sprite the frame belongs to
+/// * `frame_image` - The frame image as dumped by GODump
+fn blit_sprite(atlas: &mut DynamicImage, sprite: &Sprite, frame_image: &DynamicImage) {
+    let atlas_width = atlas.width() as i32;
+    let atlas_height = atlas.height() as i32;
+    for i in sprite.xr..(sprite.xr + sprite.width) {
+        for j in sprite.yr..(sprite.yr + sprite.height) {
+            if i < 0 || i >= frame_image.width() as i32 || j < 0 || j >= frame_image.height() as i32
+            {
+                continue;
+            }
+            let (x, y) = atlas_position(sprite, i, j, atlas_height);
+            if x >= 0 && x < atlas_width && y >= 0 && y < atlas_height {
+                atlas.put_pixel(
+                    x as u32,
+                    y as u32,
+                    frame_image.get_pixel(i as u32, (frame_image.height() as i32 - j - 1) as u32),
+                );
+            }
+        }
+    }
+}
@@ -1,9 +1,8 @@
 use std::{
     collections::HashMap,
-    fs,
     path::{Path, PathBuf},
-    str::FromStr,
     sync::{
+        atomic::{AtomicUsize, Ordering},
         mpsc::{self, Receiver, Sender},
         Arc, Mutex,
     },
@@ -12,30 +11,49 @@ use std::{
 };
 
 use eframe::{
-    egui::{self, Button, FontData, ProgressBar, ScrollArea, SelectableLabel, TextEdit},
-    emath::Vec2b,
-    epaint::Vec2,
+    egui::{
+        self, load::SizedTexture, Button, ColorImage, DragValue, FontData, Key, KeyboardShortcut,
+        Modifiers, ProgressBar, ScrollArea, SelectableLabel, TextEdit, TextureHandle,
+        TextureOptions,
+    },
+    emath::{Rect, Vec2b},
+    epaint::{Color32, Stroke, Vec2},
     glow,
 };
-use image::{GenericImage, GenericImageView};
-use notify::{event::ModifyKind, EventKind, PollWatcher, RecursiveMode, Watcher};
-use rayon::iter::{
-    IndexedParallelIterator, IntoParallelIterator, IntoParallelRefIterator, ParallelIterator,
-};
+use notify::{EventKind, PollWatcher, RecursiveMode, Watcher};
+use rayon::iter::{IntoParallelIterator, IntoParallelRefIterator, ParallelIterator};
 use serde::{Deserialize, Serialize};
 
 use crate::components::switch::switch;
-use crate::tk2d::{
-    anim::Animation,
-    clip::Clip,
-    cln::Collection,
-    info::{AnimInfo, SpriteInfo},
-    sprite::{Sprite, SpriteImage},
+use spritepacker::error::{Result, SpritePackerError};
+use spritepacker::packer::{
+    backup::{self, Backup, BackupFile, BACKUPS_DIR_NAME},
+    cache::{HashCache, CACHE_FILE_NAME},
+    check,
+    diff::{self, SpriteDiff},
+    history::{History, Operation},
+    load::{self, Dump},
+    pack::{self, Layout, PackReport},
+    project::{Project, PROJECT_FILE_NAME},
+    replace::{self, FileReport},
+    resolve::{self, Strategy},
+    trim::ExceedingFrame,
 };
+use spritepacker::tk2d::{anim::Animation, clip::Clip, cln::Collection, sprite::Sprite};
 
-use super::{i18n::translate, settings::Settings};
+use super::{
+    i18n::translate,
+    jobs::{JobContext, JobKind, JobMessage, JobResult, JobStatus, Jobs},
+    settings::Settings,
+    writes::OwnWrites,
+};
 
 const APP_NAME: &str = "spritepacker";
+const UNDO_SHORTCUT: KeyboardShortcut = KeyboardShortcut::new(Modifiers::COMMAND, Key::Z);
+const REDO_SHORTCUTS: [KeyboardShortcut; 2] = [
+    KeyboardShortcut::new(Modifiers::COMMAND.plus(Modifiers::SHIFT), Key::Z),
+    KeyboardShortcut::new(Modifiers::COMMAND, Key::Y),
+];
 
 #[derive(Default, Deserialize, Serialize, PartialEq)]
 enum InspectMode {
@@ -43,13 +61,36 @@ enum InspectMode {
     Animation,
     Backup,
     Collection,
+    Diff,
+    Trim,
+}
+
+/// The files that an operation would write, shown for confirmation before it is performed.
+pub enum Preview {
+    Replace {
+        source_sprites: Vec<Sprite>,
+        operation_name: String,
+        reports: Vec<FileReport>,
+    },
+    Pack {
+        collection_name: String,
+        layout: Layout,
+        report: PackReport,
+    },
+}
+
+/// A file in the sprites folder that the watcher saw change.
+enum WatchEvent {
+    /// The file was created, or its contents or metadata changed
+    Changed(PathBuf),
+    /// The file was deleted or renamed to something else
+    Removed(PathBuf),
 }
 
 #[derive(Default, Deserialize, Serialize)]
 struct AppState {
     pub loaded_collections: Vec<Collection>,
     pub loaded_animations: Vec<Animation>,
-    pub settings: Settings,
 
     pub current_animation: Animation,
     pub current_clip: Clip,
@@ -57,10 +98,20 @@ struct AppState {
     pub current_frame: Sprite,
     pub current_frame_index: usize,
     pub changed_sprites: Vec<Sprite>,
+    pub exceeding_frames: Vec<ExceedingFrame>,
+    pub edited_frames: Vec<Sprite>,
+    /// Frames changed on disk since the last check, with the name to list each under
+    pub modified_sprites: Vec<(Sprite, String)>,
+    pub diff_sprite: Sprite,
     pub pack_progress: f32,
     pub can_pack: bool,
     pub is_checking: bool,
     pub is_packing: bool,
+    pub is_packing_all: bool,
+    pub show_backups: bool,
+    pub show_history: bool,
+    pub show_jobs: bool,
+    pub show_project: bool,
     pub inspect_mode: InspectMode,
 
     pub animations_filter: String,
@@ -68,119 +119,298 @@ struct AppState {
     pub frames_filter: String,
 }
 
-pub struct App {
+/// Everything the app keeps for a single dump open in a tab.
+/// Anything that belongs to a dump rather than to the app as a whole goes here, so that it never leaks into another tab.
+#[derive(Default)]
+struct TabState {
     state: AppState,
     frame_timer: Option<Instant>,
-    progress_sender: Option<Sender<f32>>,
-    progress_receiver: Option<Receiver<f32>>,
-    sprite_receiver: Option<Receiver<Sprite>>,
+    jobs: Jobs,
+    /// The ID of the job loading the dump, while it is running
+    load_job: Option<usize>,
+    /// The sprites path the dump was last loaded from
+    loaded_path: String,
+    watcher_receiver: Option<Receiver<WatchEvent>>,
+    sprite_diff: Option<(SpriteDiff, TextureHandle)>,
+    original_texture: Option<TextureHandle>,
+    backups: Vec<Backup>,
+    history: History,
+    preview: Option<Preview>,
     watcher: Option<PollWatcher>,
+    /// The files the app is writing or has written, which the watcher does not report as changes
+    own_writes: Arc<Mutex<OwnWrites>>,
+    /// The files that each running job may write, by job ID
+    job_writes: HashMap<usize, Vec<PathBuf>>,
+    hash_cache: Arc<HashCache>,
+    /// The options of the dump, from its project file
+    project: Project,
+}
+
+pub struct App {
+    /// Every open dump. The shown tab is moved into `tab`, leaving an empty placeholder at `current_tab`
+    tabs: Vec<TabState>,
+    current_tab: usize,
+    /// The dump of the shown tab
+    tab: TabState,
+    /// The settings shared by every tab
+    settings: Settings,
+    /// The settings as they were last stored, so that they are stored again as soon as they change
+    stored_settings: Settings,
+    error_sender: Sender<SpritePackerError>,
+    error_receiver: Receiver<SpritePackerError>,
+    errors: Vec<SpritePackerError>,
 }
 
 impl eframe::App for App {
     fn on_exit(&mut self, _gl: Option<&glow::Context>) {
-        confy::store(APP_NAME, APP_NAME, &self.state.settings).expect("Failed to store settings");
+        self.tab.jobs.cancel_all();
+        for tab in &self.tabs {
+            tab.jobs.cancel_all();
+        }
+        self.store_settings();
     }
 
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         self.check_frame_timer();
 
-        if self.state.is_checking {
-            self.poll_changed_sprites();
-        }
+        self.poll_jobs();
+        self.poll_watcher(ctx);
+        self.poll_errors();
+        self.store_settings();
+        self.handle_shortcuts(ctx);
 
-        ctx.set_visuals(if self.state.settings.dark {
+        ctx.set_visuals(if self.settings.dark {
             egui::Visuals::dark()
         } else {
             egui::Visuals::light()
         });
 
         egui::TopBottomPanel::new(egui::panel::TopBottomSide::Top, "topbar").show(ctx, |ui| {
-            ui.heading(translate("Settings", self.state.settings.language.clone()));
+            ui.heading(translate("Settings", self.settings.language.clone()));
             ui.horizontal(|ui| {
-                ui.label(translate("Dark", self.state.settings.language.clone()));
-                let dark_mode_switch = switch(&mut self.state.settings.dark);
+                ui.label(translate("Dark", self.settings.language.clone()));
+                let dark_mode_switch = switch(&mut self.settings.dark);
                 ui.add(dark_mode_switch);
 
-                ui.label(translate(
-                    "Sprites Path",
-                    self.state.settings.language.clone(),
-                ));
-                ui.text_edit_singleline(&mut self.state.settings.sprites_path);
-                if ui
-                    .button(translate("Browse", self.state.settings.language.clone()))
-                    .clicked()
-                {
+                ui.label(translate("Sprites Path", self.settings.language.clone()));
+                let ui_enabled = self.ui_enabled();
+                let text_edit = TextEdit::singleline(&mut self.settings.sprites_path);
+                let response = ui.add_enabled(ui_enabled, text_edit);
+                // Only reload once the path has been typed out, not on every keystroke
+                if response.lost_focus() && self.settings.sprites_path != self.tab.loaded_path {
+                    self.reload();
+                }
+                let button = Button::new(translate("Browse", self.settings.language.clone()));
+                if ui.add_enabled(self.ui_enabled(), button).clicked() {
                     if let Some(path) = rfd::FileDialog::new().pick_folder() {
-                        self.state.settings.sprites_path = path
-                            .to_str()
-                            .expect("Failed to convert path to str")
-                            .to_string();
+                        self.settings.sprites_path = path.to_string_lossy().to_string();
+                        self.reload();
                     }
                 }
+                let button = Button::new(translate("Reload", self.settings.language.clone()));
+                if ui.add_enabled(self.ui_enabled(), button).clicked() {
+                    self.reload();
+                }
 
-                ui.label(translate("Language", self.state.settings.language.clone()));
+                ui.label(translate("Language", self.settings.language.clone()));
                 egui::ComboBox::new("languageselect", "")
-                    .selected_text(self.state.settings.language.clone())
+                    .selected_text(self.settings.language.clone())
                     .show_ui(ui, |ui| {
                         ui.selectable_value(
-                            &mut self.state.settings.language,
+                            &mut self.settings.language,
                             "de".to_string(),
                             "Deutsch",
                         );
                         ui.selectable_value(
-                            &mut self.state.settings.language,
+                            &mut self.settings.language,
                             "en-US".to_string(),
                             "English",
                         );
                         ui.selectable_value(
-                            &mut self.state.settings.language,
+                            &mut self.settings.language,
                             "es".to_string(),
                             "Español",
                         );
                         ui.selectable_value(
-                            &mut self.state.settings.language,
+                            &mut self.settings.language,
                             "fr".to_string(),
                             "Français",
                         );
                         ui.selectable_value(
-                            &mut self.state.settings.language,
+                            &mut self.settings.language,
                             "zh-CN".to_string(),
                             "中文",
                         );
                     });
+
+                let button = SelectableLabel::new(
+                    self.tab.state.show_backups,
+                    translate("Backups", self.settings.language.clone()),
+                );
+                if ui.add(button).clicked() {
+                    self.tab.state.show_backups = !self.tab.state.show_backups;
+                    if self.tab.state.show_backups {
+                        self.load_backups();
+                    }
+                }
+
+                let button = SelectableLabel::new(
+                    self.tab.state.show_history,
+                    translate("History", self.settings.language.clone()),
+                );
+                if ui.add(button).clicked() {
+                    self.tab.state.show_history = !self.tab.state.show_history;
+                }
+
+                let button = SelectableLabel::new(
+                    self.tab.state.show_jobs,
+                    translate("Jobs", self.settings.language.clone()),
+                );
+                if ui.add(button).clicked() {
+                    self.tab.state.show_jobs = !self.tab.state.show_jobs;
+                }
+
+                let button = SelectableLabel::new(
+                    self.tab.state.show_project,
+                    translate("Project", self.settings.language.clone()),
+                );
+                if ui.add(button).clicked() {
+                    self.tab.state.show_project = !self.tab.state.show_project;
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label(translate(
+                    "Output Directory",
+                    self.settings.language.clone(),
+                ));
+                ui.text_edit_singleline(&mut self.settings.output_dir);
+                if ui
+                    .button(translate("Browse", self.settings.language.clone()))
+                    .clicked()
+                {
+                    if let Some(path) = rfd::FileDialog::new().pick_folder() {
+                        self.settings.output_dir = path.to_string_lossy().to_string();
+                    }
+                }
+
+                ui.label(translate(
+                    "Ask Where To Save",
+                    self.settings.language.clone(),
+                ));
+                let ask_save_path_switch = switch(&mut self.settings.ask_save_path);
+                ui.add(ask_save_path_switch);
+
+                ui.label(translate("Preview Changes", self.settings.language.clone()));
+                let preview_changes_switch = switch(&mut self.settings.preview_changes);
+                ui.add(preview_changes_switch);
+            });
+            ui.horizontal(|ui| {
+                ui.label(translate("New Layout", self.settings.language.clone()));
+                let repack_switch = switch(&mut self.settings.repack);
+                ui.add(repack_switch);
+
+                ui.label(translate("Max Atlas Size", self.settings.language.clone()));
+                let max_atlas_size =
+                    DragValue::new(&mut self.settings.max_atlas_size).clamp_range(1..=16384);
+                ui.add_enabled(self.settings.repack, max_atlas_size);
+
+                ui.label(translate("Padding", self.settings.language.clone()));
+                let padding = DragValue::new(&mut self.settings.padding).clamp_range(0..=64);
+                ui.add_enabled(self.settings.repack, padding);
+
+                ui.label(translate("Re-trim", self.settings.language.clone()));
+                let retrim_switch = switch(&mut self.settings.retrim);
+                ui.add_enabled(self.settings.repack, retrim_switch);
             });
+            ui.separator();
+            self.show_tabs(ui);
         });
+        if !self.errors.is_empty() {
+            egui::TopBottomPanel::new(egui::panel::TopBottomSide::Bottom, "errorspanel")
+                .resizable(true)
+                .show(ctx, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.heading(translate("Errors", self.settings.language.clone()));
+                        if ui
+                            .button(translate("Dismiss All", self.settings.language.clone()))
+                            .clicked()
+                        {
+                            self.errors.clear();
+                        }
+                    });
+                    ui.separator();
+                    let mut dismissed = None;
+                    ScrollArea::new(Vec2b::new(false, true)).show(ui, |ui| {
+                        for (index, error) in self.errors.iter().enumerate() {
+                            ui.horizontal(|ui| {
+                                if ui.small_button("✖").clicked() {
+                                    dismissed = Some(index);
+                                }
+                                ui.label(error.to_string());
+                            });
+                        }
+                    });
+                    if let Some(index) = dismissed {
+                        self.errors.remove(index);
+                    }
+                });
+        }
+        if self.tab.state.show_backups {
+            self.show_backups(ctx);
+        }
+        if self.tab.state.show_project {
+            self.show_project(ctx);
+        }
+        if self.tab.preview.is_some() {
+            self.show_preview(ctx);
+        }
         egui::SidePanel::new(egui::panel::Side::Left, "animationspanel")
             .default_width(150.)
             .show(ctx, |ui| {
-                ui.heading(translate(
-                    "Animations",
-                    self.state.settings.language.clone(),
-                ));
-                let filter = TextEdit::singleline(&mut self.state.animations_filter);
-                ui.add_enabled(!self.state.is_packing && !self.state.is_checking, filter);
+                ui.heading(translate("Animations", self.settings.language.clone()));
+                if let Some(job) = self.tab.load_job.and_then(|id| self.tab.jobs.get(id)) {
+                    let progress = job.progress.unwrap_or_default();
+                    let progress_bar = ProgressBar::new(progress).animate(true).text(format!(
+                        "{}: {:.0}%",
+                        translate("Loading", self.settings.language.clone()),
+                        progress * 100.
+                    ));
+                    ui.add(progress_bar);
+                }
+                let filter = TextEdit::singleline(&mut self.tab.state.animations_filter);
+                ui.add_enabled(
+                    !self.tab.state.is_packing && !self.tab.state.is_checking,
+                    filter,
+                );
                 ui.separator();
                 egui::ScrollArea::new(Vec2b::new(false, true)).show(ui, |ui| {
-                    for animation in self.state.loaded_animations.iter() {
+                    for animation in self.tab.state.loaded_animations.iter() {
                         if !animation
                             .name
                             .to_lowercase()
-                            .contains(&self.state.animations_filter.to_lowercase())
+                            .contains(&self.tab.state.animations_filter.to_lowercase())
                         {
                             continue;
                         }
                         let list_item = SelectableLabel::new(
-                            self.state.current_animation == *animation,
+                            self.tab.state.current_animation == *animation,
                             animation.name.clone(),
                         );
                         if ui.add_enabled(self.ui_enabled(), list_item).clicked() {
-                            self.frame_timer = Some(Instant::now());
-                            self.state.current_animation = animation.clone();
-                            self.state.current_clip = self.state.current_animation.clips[0].clone();
-                            self.state.current_frame = self.state.current_clip.frames[0].clone();
-                            self.state.current_frame_index = 0;
-                            self.state.inspect_mode = InspectMode::Animation;
+                            self.tab.frame_timer = Some(Instant::now());
+                            self.tab.state.current_animation = animation.clone();
+                            self.tab.state.current_clip =
+                                animation.clips.first().cloned().unwrap_or_default();
+                            self.tab.state.current_frame = self
+                                .tab
+                                .state
+                                .current_clip
+                                .frames
+                                .first()
+                                .cloned()
+                                .unwrap_or_default();
+                            self.tab.state.current_frame_index = 0;
+                            self.tab.state.inspect_mode = InspectMode::Animation;
                         }
                     }
                 });
@@ -188,29 +418,33 @@ impl eframe::App for App {
         egui::SidePanel::new(egui::panel::Side::Left, "clipspanel")
             .default_width(150.)
             .show(ctx, |ui| {
-                ui.heading(translate("Clips", self.state.settings.language.clone()));
-                let filter = TextEdit::singleline(&mut self.state.clips_filter);
-                ui.add_enabled(!self.state.is_packing && !self.state.is_checking, filter);
+                ui.heading(translate("Clips", self.settings.language.clone()));
+                let filter = TextEdit::singleline(&mut self.tab.state.clips_filter);
+                ui.add_enabled(
+                    !self.tab.state.is_packing && !self.tab.state.is_checking,
+                    filter,
+                );
                 ui.separator();
                 egui::ScrollArea::new(Vec2b::new(false, true)).show(ui, |ui| {
-                    for clip in self.state.current_animation.clips.iter() {
+                    for clip in self.tab.state.current_animation.clips.iter() {
                         if !clip
                             .name
                             .to_lowercase()
-                            .contains(&self.state.clips_filter.to_lowercase())
+                            .contains(&self.tab.state.clips_filter.to_lowercase())
                         {
                             continue;
                         }
                         let list_item = SelectableLabel::new(
-                            self.state.current_clip == *clip,
+                            self.tab.state.current_clip == *clip,
                             clip.name.clone(),
                         );
                         if ui.add_enabled(self.ui_enabled(), list_item).clicked() {
-                            self.frame_timer = Some(Instant::now());
-                            self.state.current_clip = clip.clone();
-                            self.state.current_frame = self.state.current_clip.frames[0].clone();
-                            self.state.current_frame_index = 0;
-                            self.state.inspect_mode = InspectMode::Animation;
+                            self.tab.frame_timer = Some(Instant::now());
+                            self.tab.state.current_clip = clip.clone();
+                            self.tab.state.current_frame =
+                                clip.frames.first().cloned().unwrap_or_default();
+                            self.tab.state.current_frame_index = 0;
+                            self.tab.state.inspect_mode = InspectMode::Animation;
                         }
                     }
                 });
@@ -218,192 +452,386 @@ impl eframe::App for App {
         egui::SidePanel::new(egui::panel::Side::Left, "framespanel")
             .default_width(150.)
             .show(ctx, |ui| {
-                ui.heading(translate("Frames", self.state.settings.language.clone()));
-                let filter = TextEdit::singleline(&mut self.state.frames_filter);
-                ui.add_enabled(!self.state.is_packing && !self.state.is_checking, filter);
+                ui.heading(translate("Frames", self.settings.language.clone()));
+                let filter = TextEdit::singleline(&mut self.tab.state.frames_filter);
+                ui.add_enabled(
+                    !self.tab.state.is_packing && !self.tab.state.is_checking,
+                    filter,
+                );
                 ui.separator();
                 egui::ScrollArea::new(Vec2b::new(false, true)).show(ui, |ui| {
-                    for frame in self.state.current_clip.frames.iter() {
+                    for (frame_index, frame) in
+                        self.tab.state.current_clip.frames.iter().enumerate()
+                    {
                         if !frame
                             .name
                             .to_lowercase()
-                            .contains(&self.state.frames_filter.to_lowercase())
+                            .contains(&self.tab.state.frames_filter.to_lowercase())
                         {
                             continue;
                         }
-                        let list_item = SelectableLabel::new(
-                            self.state.current_frame == *frame,
-                            frame.name.clone(),
-                        );
+                        let label = self
+                            .modified_label(frame)
+                            .unwrap_or_else(|| frame.name.clone());
+                        let list_item =
+                            SelectableLabel::new(self.tab.state.current_frame == *frame, label);
                         if ui.add_enabled(self.ui_enabled(), list_item).clicked() {
-                            self.frame_timer = None;
-                            self.state.current_frame = frame.clone();
-                            self.state.current_frame_index = self
-                                .state
-                                .current_clip
-                                .frames
-                                .iter()
-                                .position(|f| f == frame)
-                                .expect("Failed to get frame index");
-                            self.state.inspect_mode = InspectMode::Animation;
+                            self.tab.frame_timer = None;
+                            self.tab.state.current_frame = frame.clone();
+                            self.tab.state.current_frame_index = frame_index;
+                            self.tab.state.inspect_mode = InspectMode::Animation;
                         }
                     }
                 });
             });
+        if !self.tab.state.exceeding_frames.is_empty() {
+            egui::SidePanel::new(egui::panel::Side::Right, "exceedingpanel")
+                .default_width(150.)
+                .show(ctx, |ui| {
+                    ui.heading(translate(
+                        "Outside Trim Bounds",
+                        self.settings.language.clone(),
+                    ));
+                    ui.separator();
+                    let mut selected = None;
+                    egui::ScrollArea::new(Vec2b::new(false, true)).show(ui, |ui| {
+                        for exceeding_frame in self.tab.state.exceeding_frames.iter() {
+                            let sprite = &exceeding_frame.sprite;
+                            let list_item = SelectableLabel::new(
+                                self.tab.state.inspect_mode == InspectMode::Trim
+                                    && self.tab.state.current_frame == *sprite,
+                                sprite.name.clone(),
+                            );
+                            if ui.add_enabled(self.ui_enabled(), list_item).clicked() {
+                                self.tab.frame_timer = None;
+                                self.tab.state.inspect_mode = InspectMode::Trim;
+                                selected = Some(sprite.clone());
+                            }
+                        }
+                    });
+                    if let Some(sprite) = selected {
+                        self.select_changed_sprite(&sprite);
+                        self.tab.state.current_frame = sprite;
+                    }
+                });
+        }
+        if !self.tab.state.edited_frames.is_empty() {
+            egui::SidePanel::new(egui::panel::Side::Right, "editedpanel")
+                .default_width(150.)
+                .show(ctx, |ui| {
+                    ui.heading(translate("Edited Frames", self.settings.language.clone()));
+                    ui.separator();
+                    let mut selected = None;
+                    egui::ScrollArea::new(Vec2b::new(false, true)).show(ui, |ui| {
+                        for sprite in self.tab.state.edited_frames.iter() {
+                            let list_item = SelectableLabel::new(
+                                self.tab.state.inspect_mode == InspectMode::Diff
+                                    && self.tab.original_texture.is_some()
+                                    && self.tab.state.current_frame == *sprite,
+                                sprite.name.clone(),
+                            );
+                            if ui.add_enabled(self.ui_enabled(), list_item).clicked() {
+                                selected = Some(sprite.clone());
+                            }
+                        }
+                    });
+                    if let Some(sprite) = selected {
+                        self.select_changed_sprite(&sprite);
+                        self.compare_with_atlas(ui.ctx(), sprite);
+                    }
+                });
+        }
+        if self.tab.state.show_history {
+            egui::SidePanel::new(egui::panel::Side::Right, "historypanel")
+                .default_width(150.)
+                .show(ctx, |ui| self.show_history(ui));
+        }
+        if self.tab.state.show_jobs {
+            egui::SidePanel::new(egui::panel::Side::Right, "jobspanel")
+                .default_width(180.)
+                .show(ctx, |ui| self.show_jobs(ui));
+        }
         egui::SidePanel::new(egui::panel::Side::Right, "changedpanel")
             .default_width(150.)
             .show(ctx, |ui| {
-                ui.heading(translate("Changed", self.state.settings.language.clone()));
+                ui.heading(translate("Changed", self.settings.language.clone()));
                 ui.separator();
+                let mut selected = None;
                 egui::ScrollArea::new(Vec2b::new(false, true))
-                    .max_height(ui.available_height() - 64.)
+                    .max_height(ui.available_height() - 192.)
                     .show(ui, |ui| {
-                        for sprite in self.state.changed_sprites.iter() {
+                        for sprite in self.tab.state.changed_sprites.iter() {
+                            let label = if self.tab.state.is_checking {
+                                sprite.name.clone()
+                            } else if let Some(label) = self.modified_label(sprite) {
+                                label
+                            } else if self.tab.state.edited_frames.contains(sprite) {
+                                format!(
+                                    "{} ({})",
+                                    sprite.name,
+                                    translate("Edited", self.settings.language.clone())
+                                )
+                            } else {
+                                format!(
+                                    "{} ({})",
+                                    sprite.name,
+                                    translate("Original", self.settings.language.clone())
+                                )
+                            };
+                            // The duplicate kept last time is only a hint, as the strategy decides which one to keep
+                            let is_resolution = self.tab.project.is_resolution(sprite);
+                            let label = if is_resolution {
+                                format!("★ {}", label)
+                            } else {
+                                label
+                            };
                             let list_item = SelectableLabel::new(
-                                self.state.current_frame == *sprite,
-                                sprite.name.clone(),
+                                self.tab.state.current_frame == *sprite,
+                                label,
                             );
-                            if ui.add_enabled(self.ui_enabled(), list_item).clicked() {
-                                self.frame_timer = None;
-                                self.state.inspect_mode = InspectMode::Backup;
-                                let collection =
-                                    self.get_collection(sprite.collection_name.clone());
-                                self.state.current_collection = collection.clone();
-                                let animation = self.get_animation_from_collection_name(collection);
-                                self.state.current_animation = animation.clone();
-                                let clip = animation
-                                    .clips
-                                    .par_iter()
-                                    .find_first(|clip| {
-                                        clip.frames
-                                            .par_iter()
-                                            .find_first(|frame| frame.name == sprite.name)
-                                            .is_some()
-                                    })
-                                    .expect("Failed to find clip from sprite")
-                                    .clone();
-                                self.state.current_clip = clip.clone();
-                                self.state.current_frame = clip
-                                    .frames
-                                    .par_iter()
-                                    .find_first(|frame| frame.name == sprite.name)
-                                    .expect("Failed to find frame from sprite")
-                                    .clone();
+                            let response = ui.add_enabled(self.ui_enabled(), list_item);
+                            let response = if is_resolution {
+                                response.on_hover_text(translate(
+                                    "Kept The Last Time This Sprite Was Resolved",
+                                    self.settings.language.clone(),
+                                ))
+                            } else {
+                                response
+                            };
+                            if response.clicked() {
+                                self.tab.frame_timer = None;
+                                self.tab.state.inspect_mode = InspectMode::Backup;
+                                selected = Some(sprite.clone());
                             }
                         }
                     });
+                if let Some(sprite) = selected {
+                    self.select_changed_sprite(&sprite);
+                }
+                let duplicates = self.changed_duplicates(&self.tab.state.current_frame);
                 let button =
-                    egui::Button::new(translate("Replace", self.state.settings.language.clone()));
+                    egui::Button::new(translate("Compare", self.settings.language.clone()));
                 if ui
-                    .add_enabled(self.state.changed_sprites.len() > 0, button)
+                    .add_enabled(self.ui_enabled() && !duplicates.is_empty(), button)
                     .clicked()
                 {
-                    self.replace_duplicate_sprites(self.state.current_frame.clone());
+                    self.compare_sprites(
+                        ui.ctx(),
+                        self.tab.state.current_frame.clone(),
+                        duplicates[0].clone(),
+                    );
                 }
-            });
-        egui::CentralPanel::default().show(ctx, |ui| {
-            ui.heading(translate("Inspector", self.state.settings.language.clone()));
-            ui.separator();
-            let preview_url = if self.state.inspect_mode == InspectMode::Animation
-                || self.state.inspect_mode == InspectMode::Backup
-            {
-                let current_frame = self.state.current_frame.clone();
-                let frame_path = current_frame.path.clone();
-                let mut frame_url = format!("file://{frame_path}");
-                if !Path::new(&frame_path).exists() {
-                    frame_url = format!(
-                        "file://{}/{}",
-                        self.state.settings.sprites_path.clone(),
-                        frame_path
+                let button = egui::Button::new(translate(
+                    "Compare With Original",
+                    self.settings.language.clone(),
+                ));
+                if ui
+                    .add_enabled(
+                        self.ui_enabled() && self.tab.state.current_frame != Sprite::default(),
+                        button,
+                    )
+                    .clicked()
+                {
+                    self.compare_with_atlas(ui.ctx(), self.tab.state.current_frame.clone());
+                }
+                let button =
+                    egui::Button::new(translate("Replace", self.settings.language.clone()));
+                if ui
+                    .add_enabled(
+                        self.ui_enabled() && !self.tab.state.changed_sprites.is_empty(),
+                        button,
+                    )
+                    .clicked()
+                {
+                    let operation_name = format!(
+                        "{} {}",
+                        translate("Replace", self.settings.language.clone()),
+                        self.tab.state.current_frame.name
+                    );
+                    self.replace_or_preview(
+                        vec![self.tab.state.current_frame.clone()],
+                        operation_name,
                     );
                 }
-
-                frame_url
-            } else if self.state.inspect_mode == InspectMode::Collection {
-                let current_collection = self.state.current_collection.clone();
-                let collection_path = current_collection.path.clone();
-                let mut collection_url = format!("file://{}", collection_path.display());
-                if !Path::new(&collection_path).exists() {
-                    collection_url = format!(
-                        "file://{}/{}",
-                        self.state.settings.sprites_path.clone(),
-                        collection_path.display()
+                ui.separator();
+                let language = self.settings.language.clone();
+                let strategy_name = |strategy: Strategy| match strategy {
+                    Strategy::Newest => translate("Newest", language.clone()),
+                    Strategy::DiffersFromAtlas => translate("Differs From Atlas", language.clone()),
+                    Strategy::Majority => translate("Majority", language.clone()),
+                };
+                egui::ComboBox::new("strategyselect", "")
+                    .selected_text(strategy_name(self.settings.resolve_strategy))
+                    .show_ui(ui, |ui| {
+                        for strategy in [
+                            Strategy::Newest,
+                            Strategy::DiffersFromAtlas,
+                            Strategy::Majority,
+                        ] {
+                            ui.selectable_value(
+                                &mut self.settings.resolve_strategy,
+                                strategy,
+                                strategy_name(strategy),
+                            );
+                        }
+                    });
+                let button = egui::Button::new(translate("Resolve", language.clone()));
+                if ui
+                    .add_enabled(
+                        self.ui_enabled()
+                            && self
+                                .tab
+                                .state
+                                .changed_sprites
+                                .contains(&self.tab.state.current_frame),
+                        button,
+                    )
+                    .clicked()
+                {
+                    let current_frame = self.tab.state.current_frame.clone();
+                    self.choose_sources(vec![current_frame], None);
+                }
+                let button = egui::Button::new(translate("Resolve All", language.clone()));
+                if ui
+                    .add_enabled(
+                        self.ui_enabled() && !self.tab.state.changed_sprites.is_empty(),
+                        button,
+                    )
+                    .clicked()
+                {
+                    let changed_sprites = self.tab.state.changed_sprites.clone();
+                    self.choose_sources(
+                        changed_sprites,
+                        Some(translate("Resolve All", language.clone()).to_string()),
                     );
                 }
-
-                collection_url
+            });
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.heading(translate("Inspector", self.settings.language.clone()));
+            ui.separator();
+            let preview_url = if self.tab.state.inspect_mode == InspectMode::Animation
+                || self.tab.state.inspect_mode == InspectMode::Backup
+                || self.tab.state.inspect_mode == InspectMode::Trim
+            {
+                self.frame_url(&self.tab.state.current_frame.path)
+            } else if self.tab.state.inspect_mode == InspectMode::Collection {
+                self.atlas_url(&self.tab.state.current_collection)
             } else {
                 "".to_string()
             };
 
-            let preview_image = egui::Image::new(preview_url)
-                .max_size(Vec2::new(256., 256.))
-                .maintain_aspect_ratio(true);
-            ui.add(preview_image);
+            if self.tab.state.inspect_mode == InspectMode::Diff {
+                self.show_sprite_diff(ui);
+            } else {
+                let preview_image = egui::Image::new(preview_url)
+                    .max_size(Vec2::new(256., 256.))
+                    .maintain_aspect_ratio(true);
+                let preview_response = ui.add(preview_image);
+                if self.tab.state.inspect_mode == InspectMode::Trim {
+                    if let Some(exceeding_frame) = self
+                        .tab
+                        .state
+                        .exceeding_frames
+                        .iter()
+                        .find(|frame| frame.sprite == self.tab.state.current_frame)
+                    {
+                        App::paint_trim_overlay(ui, preview_response.rect, exceeding_frame);
+                    }
+                }
+            }
 
             ScrollArea::new(Vec2b::new(false, true))
                 .max_height(ui.available_height())
                 .show(ui, |ui| {
-                    for collection in self.state.loaded_collections.iter() {
+                    for collection in self.tab.state.loaded_collections.iter() {
                         let list_item = SelectableLabel::new(
-                            self.state.current_collection == *collection,
+                            self.tab.state.current_collection == *collection,
                             collection.name.clone(),
                         );
                         if ui.add_enabled(self.ui_enabled(), list_item).clicked() {
-                            self.frame_timer = None;
-                            self.state.can_pack = false;
-                            self.state.current_collection = collection.clone();
-                            self.state.inspect_mode = InspectMode::Collection;
+                            self.tab.frame_timer = None;
+                            self.tab.state.can_pack = false;
+                            self.tab.state.current_collection = collection.clone();
+                            self.tab.state.inspect_mode = InspectMode::Collection;
                         }
                     }
                 });
 
-            if !self.state.is_packing {
-                if !self.state.can_pack {
-                    let button =
-                        Button::new(translate("Check", self.state.settings.language.clone()));
+            if !self.tab.state.is_packing {
+                ui.horizontal(|ui| {
+                    if !self.tab.state.can_pack {
+                        let button =
+                            Button::new(translate("Check", self.settings.language.clone()));
+                        if ui
+                            .add_enabled(
+                                self.tab.state.inspect_mode == InspectMode::Collection
+                                    && !self.tab.state.is_checking
+                                    && self.tab.load_job.is_none(),
+                                button,
+                            )
+                            .clicked()
+                        {
+                            self.start_check();
+                        }
+                        if self.tab.state.is_checking {
+                            let button =
+                                Button::new(translate("Cancel", self.settings.language.clone()));
+                            if ui.add(button).clicked() {
+                                self.tab.jobs.cancel_kind(JobKind::Check);
+                            }
+                        }
+                    } else {
+                        let button = Button::new(translate("Pack", self.settings.language.clone()));
+                        if ui
+                            .add_enabled(self.tab.state.changed_sprites.is_empty(), button)
+                            .clicked()
+                        {
+                            self.tab.state.can_pack = false;
+                            self.tab.state.is_packing = true;
+                            self.tab.state.is_packing_all = false;
+                            self.tab.state.pack_progress = 0.;
+                            self.pack_single_collection(
+                                self.tab.state.current_collection.name.clone(),
+                            );
+                        }
+                    }
+                    let button = Button::new(translate("Pack All", self.settings.language.clone()));
                     if ui
                         .add_enabled(
-                            self.state.inspect_mode == InspectMode::Collection
-                                && !self.state.is_checking,
+                            !self.tab.state.is_checking
+                                && self.tab.load_job.is_none()
+                                && !self.tab.state.loaded_collections.is_empty(),
                             button,
                         )
                         .clicked()
                     {
-                        self.state.is_checking = true;
-                        self.state.changed_sprites = vec![];
-                        let sprites_path = self.state.settings.sprites_path.clone();
-                        let mut collections = self.state.loaded_collections.clone();
-                        let (tx_sprite, rx_sprite) = mpsc::channel();
-                        self.sprite_receiver = Some(rx_sprite);
-                        thread::spawn(move || {
-                            App::check(sprites_path, &mut collections, tx_sprite)
-                        });
-                    }
-                } else {
-                    let button =
-                        Button::new(translate("Pack", self.state.settings.language.clone()));
-                    if ui
-                        .add_enabled(self.state.changed_sprites.len() <= 0, button)
-                        .clicked()
-                    {
-                        self.state.can_pack = false;
-                        self.state.is_packing = true;
-                        self.state.pack_progress = 0.;
-                        self.pack_single_collection(self.state.current_collection.name.clone());
+                        self.tab.state.can_pack = false;
+                        self.tab.state.is_packing = true;
+                        self.tab.state.is_packing_all = true;
+                        self.tab.state.pack_progress = 0.;
+                        self.pack_all_collections();
                     }
-                }
+                });
             } else {
-                self.poll_progress();
-                let progress_bar = ProgressBar::new(self.state.pack_progress)
+                let packing_name = if self.tab.state.is_packing_all {
+                    translate("All Collections", self.settings.language.clone()).to_string()
+                } else {
+                    self.tab.state.current_collection.name.clone()
+                };
+                let progress_bar = ProgressBar::new(self.tab.state.pack_progress)
                     .animate(true)
                     .text(format!(
                         "{} {}: {:.2}%",
-                        translate("Packing", self.state.settings.language.clone()),
-                        self.state.current_collection.name,
-                        self.state.pack_progress * 100.
+                        translate("Packing", self.settings.language.clone()),
+                        packing_name,
+                        self.tab.state.pack_progress * 100.
                     ));
                 ui.add(progress_bar);
+                let button = Button::new(translate("Cancel", self.settings.language.clone()));
+                if ui.add(button).clicked() {
+                    self.tab.jobs.cancel_kind(JobKind::Pack);
+                    self.tab.jobs.cancel_kind(JobKind::Preview);
+                }
             }
         });
 
@@ -413,124 +841,38 @@ impl eframe::App for App {
 
 impl App {
     pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
+        let (error_sender, error_receiver) = mpsc::channel();
         let mut app = App {
-            state: AppState::default(),
-            frame_timer: Some(Instant::now()),
-            progress_sender: None,
-            progress_receiver: None,
-            sprite_receiver: None,
-            watcher: None,
+            tabs: vec![TabState::default()],
+            current_tab: 0,
+            tab: TabState {
+                frame_timer: Some(Instant::now()),
+                ..TabState::default()
+            },
+            settings: Settings::default(),
+            stored_settings: Settings::default(),
+            error_sender,
+            error_receiver,
+            errors: vec![],
         };
 
         // Load settings
-        if let Ok(settings) = confy::load::<Settings>(APP_NAME, APP_NAME) {
-            app.state.settings = settings;
+        match confy::load::<Settings>(APP_NAME, APP_NAME) {
+            Ok(settings) => {
+                app.stored_settings = settings.clone();
+                app.settings = settings;
+            }
+            Err(e) => app.errors.push(e.into()),
         }
 
-        while app.state.settings.sprites_path == "".to_string() {
+        while app.settings.sprites_path == "".to_string() {
             if let Some(path) = rfd::FileDialog::new().pick_folder() {
-                app.state.settings.sprites_path = path
-                    .to_str()
-                    .expect("Failed to convert path to str")
-                    .to_string();
+                app.settings.sprites_path = path.to_string_lossy().to_string();
             }
         }
 
-        app.load_collections_and_animations();
-
-        let sprites_path = app.state.settings.sprites_path.clone();
-
-        let (tx_sprite, rx_sprite) = mpsc::channel();
-
-        app.sprite_receiver = Some(rx_sprite);
-
-        let (tx_watcher, rx_watcher) = mpsc::channel();
-        let config = notify::Config::default()
-            .with_compare_contents(true)
-            .with_poll_interval(Duration::from_secs(1));
-
-        let mut watcher =
-            notify::PollWatcher::new(tx_watcher, config).expect("Failed to create watcher");
-        watcher
-            .watch(&Path::new(&sprites_path), RecursiveMode::Recursive)
-            .expect("Failed to watch sprites path");
-        app.watcher = Some(watcher);
-
-        thread::spawn(move || match rx_watcher.recv() {
-            Ok(result) => match result {
-                Ok(event) => match &event.kind {
-                    EventKind::Modify(modify_kind) => match modify_kind {
-                        ModifyKind::Metadata(_) => {
-                            println!("Event: {:?}", event);
-                            for path in &event.paths {
-                                let path = path
-                                    .strip_prefix(sprites_path.clone())
-                                    .expect("Failed to strip prefix from path");
-                                let path_string = match path.to_str() {
-                                    Some(str) => String::from(str),
-                                    None => panic!("Failed to convert path to string"),
-                                };
-                                let paths = path_string.split(['/', '\\']).collect::<Vec<&str>>();
-                                let sprite_info_path = PathBuf::from(sprites_path.clone())
-                                    .join(paths[0])
-                                    .join("0.Atlases")
-                                    .join("SpriteInfo.json");
-                                let collection_name = match fs::read_to_string(sprite_info_path) {
-                                    Ok(text) => {
-                                        let sprite_info: SpriteInfo =
-                                            match serde_json::from_str(&text) {
-                                                Ok(info) => info,
-                                                Err(e) => {
-                                                    panic!("Failed to parse SpriteInfo.json: {e}")
-                                                }
-                                            };
-                                        sprite_info.collection_name[0].to_string()
-                                    }
-                                    Err(e) => {
-                                        panic!("Failed to read SpriteInfo.json: {e}")
-                                    }
-                                };
-                                if paths.len() < 3 {
-                                    continue;
-                                }
-                                let sprite_name = paths[2].to_string();
-                                let sprite_data = sprite_name.split("-").collect::<Vec<&str>>();
-                                if sprite_data.len() < 3 {
-                                    continue;
-                                }
-                                let sprite_id_string =
-                                    sprite_data[sprite_data.len() - 1].replace(".png", "");
-                                let sprite_id = match sprite_id_string.parse::<u32>() {
-                                    Ok(id) => id,
-                                    Err(e) => {
-                                        panic!("Failed to parse sprite id {sprite_id_string}: {e}")
-                                    }
-                                };
-                                let sprite = Sprite {
-                                    id: sprite_id,
-                                    name: sprite_name.to_string(),
-                                    collection_name: collection_name.to_string(),
-                                    path: path_string,
-                                    flipped: false,
-                                    x: 0,
-                                    y: 0,
-                                    xr: 0,
-                                    yr: 0,
-                                    width: 0,
-                                    height: 0,
-                                };
-
-                                tx_sprite.send(sprite).expect("Failed to send sprite");
-                            }
-                        }
-                        _ => {}
-                    },
-                    _ => {}
-                },
-                Err(e) => panic!("Failed to receive event: {:?}", e),
-            },
-            Err(e) => panic!("Watcher error: {:?}", e),
-        });
+        app.start_load();
+        app.start_watcher();
 
         App::set_font(
             &cc.egui_ctx,
@@ -541,136 +883,269 @@ impl App {
         return app;
     }
 
-    /// Check whether any sprites and their duplicates are not identical.
-    fn check(
-        sprites_path: String,
-        collections: &mut Vec<Collection>,
-        sprite_sender: Sender<Sprite>,
-    ) {
-        let mut problem_sprites = vec![];
-        for collection in collections {
-            let mut sprite_map = HashMap::<u32, Vec<Sprite>>::new();
-            for sprite in &collection.sprites {
-                let sprite_map_entry = sprite_map.get(&sprite.id);
-                if let Some(entry) = sprite_map_entry {
-                    for existing_sprite in entry {
-                        let existing_sprite_path = existing_sprite.path.clone();
-                        let mut path1 =
-                            PathBuf::from(sprites_path.clone()).join(existing_sprite_path.clone());
-                        if !path1.exists() {
-                            path1 = PathBuf::from(existing_sprite_path.clone());
-                        }
-                        let mut path2 =
-                            PathBuf::from(sprites_path.clone()).join(sprite.path.clone());
-                        if !path2.exists() {
-                            path2 = PathBuf::from(sprite.path.clone());
-                        }
+    /// Watch the sprites folder for files that are created, changed, deleted or renamed outside of the app, replacing any previous watcher.
+    fn start_watcher(&mut self) {
+        let sprites_path = self.settings.sprites_path.clone();
 
-                        let image1 = image::open(path1.clone()).expect(
-                            format!("Failed to open image at path {:?}", path1.display()).as_str(),
-                        );
+        let (tx_event, rx_event) = mpsc::channel();
 
-                        let image2 = image::open(path2.clone()).expect(
-                            format!("Failed to open image at path {:?}", path2.display()).as_str(),
-                        );
+        self.tab.watcher_receiver = Some(rx_event);
 
-                        let sprite_image1 = SpriteImage {
-                            sprite: existing_sprite.clone(),
-                            image: image1,
-                        };
-
-                        let sprite_image2 = SpriteImage {
-                            sprite: sprite.clone(),
-                            image: image2,
-                        };
-
-                        if !sprite_image1.equals(&sprite_image2) {
-                            for sprite in entry {
-                                if !problem_sprites.contains(sprite) {
-                                    problem_sprites.push(sprite.clone());
-                                    sprite_sender
-                                        .send(sprite.clone())
-                                        .expect("Failed to send sprite");
-                                }
-                            }
+        let (tx_watcher, rx_watcher) = mpsc::channel();
+        let config = notify::Config::default()
+            .with_compare_contents(true)
+            .with_poll_interval(Duration::from_secs(1));
 
-                            if !problem_sprites.contains(sprite) {
-                                problem_sprites.push(sprite.clone());
-                                sprite_sender
-                                    .send(sprite.clone())
-                                    .expect("Failed to send sprite");
-                            }
+        // Dropping the previous watcher stops it, which also ends the thread listening to it
+        self.tab.watcher = None;
+        match notify::PollWatcher::new(tx_watcher, config) {
+            Ok(mut watcher) => {
+                if let Err(e) = watcher.watch(Path::new(&sprites_path), RecursiveMode::Recursive) {
+                    self.errors.push(e.into());
+                }
+                self.tab.watcher = Some(watcher);
+            }
+            Err(e) => self.errors.push(e.into()),
+        }
 
-                            break;
-                        }
+        let tx_error = self.error_sender.clone();
+        let hash_cache = self.tab.hash_cache.clone();
+        let own_writes = self.tab.own_writes.clone();
+        let sprites_path = PathBuf::from(sprites_path);
+        thread::spawn(move || {
+            // Runs for as long as the watcher does, as dropping it closes the channel
+            for result in rx_watcher {
+                let event = match result {
+                    Ok(event) => event,
+                    Err(e) => {
+                        let _ = tx_error.send(e.into());
+                        continue;
                     }
-                } else if sprite_map_entry.is_none() {
-                    let sprite_data = sprite.name.split("-").collect::<Vec<&str>>();
-                    let sprite_id_string = sprite_data[sprite_data.len() - 1].replace(".png", "");
-                    let sprite_id = sprite_id_string.parse::<u32>().expect(
-                        format!("Failed to convert Sprite ID string {sprite_id_string} to u32")
-                            .as_str(),
-                    );
-                    sprite_map.insert(sprite_id, vec![sprite.clone()]);
+                };
+                if matches!(event.kind, EventKind::Access(_)) {
+                    continue;
+                }
+                for path in event.paths {
+                    // The app's own files change whenever it saves them, and so do the files it writes into the dump,
+                    // neither of which is a change made outside of the app
+                    if path.starts_with(sprites_path.join(BACKUPS_DIR_NAME))
+                        || path == sprites_path.join(CACHE_FILE_NAME)
+                        || path == sprites_path.join(PROJECT_FILE_NAME)
+                        || own_writes.lock().unwrap().is_own(&path)
+                    {
+                        continue;
+                    }
+                    hash_cache.invalidate(&path);
+                    // Renames are reported differently depending on the platform, so whether the file is still there is what counts
+                    let watch_event = if path.exists() {
+                        WatchEvent::Changed(path)
+                    } else {
+                        WatchEvent::Removed(path)
+                    };
+                    if tx_event.send(watch_event).is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+    }
+
+    /// Show a tab for each open dump, along with buttons to open another one and to switch to a recently opened one.
+    /// # Arguments
+    /// * `ui` - The UI to show the tabs in
+    fn show_tabs(&mut self, ui: &mut egui::Ui) {
+        let language = self.settings.language.clone();
+        let mut switched_tab = None;
+        let mut closed_tab = None;
+        let mut opened_path = None;
+        let mut recent_path = None;
+        ui.horizontal(|ui| {
+            for index in 0..self.tabs.len() {
+                let (loaded_path, is_busy) = if index == self.current_tab {
+                    (&self.tab.loaded_path, !self.ui_enabled())
                 } else {
-                    sprite_map
-                        .get_mut(&sprite.id)
-                        .expect("Sprite map is None")
-                        .push(sprite.clone());
+                    let tab = &self.tabs[index];
+                    (&tab.loaded_path, tab.jobs.is_running_except(JobKind::Load))
+                };
+                let tab_name = Path::new(loaded_path)
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .unwrap_or(loaded_path);
+                let label = SelectableLabel::new(index == self.current_tab, tab_name);
+                if ui.add(label).on_hover_text(loaded_path).clicked() {
+                    switched_tab = Some(index);
+                }
+                // A dump that is being written to is kept open until it has finished
+                if self.tabs.len() > 1
+                    && ui.add_enabled(!is_busy, Button::new("✖").small()).clicked()
+                {
+                    closed_tab = Some(index);
                 }
+                ui.separator();
             }
-        }
+            if ui
+                .button("+")
+                .on_hover_text(translate("Open In New Tab", language.clone()))
+                .clicked()
+            {
+                if let Some(path) = rfd::FileDialog::new().pick_folder() {
+                    opened_path = Some(path.to_string_lossy().to_string());
+                }
+            }
+            let ui_enabled = self.ui_enabled();
+            ui.menu_button(translate("Recent", language.clone()), |ui| {
+                for path in &self.settings.recent_paths {
+                    if ui.add_enabled(ui_enabled, Button::new(path)).clicked() {
+                        recent_path = Some(path.clone());
+                        ui.close_menu();
+                    }
+                }
+            });
+        });
 
-        sprite_sender
-            .send(Sprite::default())
-            .expect("Failed to send cancel signal");
+        if let Some(index) = switched_tab {
+            self.switch_tab(index);
+        }
+        if let Some(index) = closed_tab {
+            self.close_tab(index);
+        }
+        if let Some(path) = opened_path {
+            self.open_tab(path);
+        }
+        if let Some(path) = recent_path {
+            match self.tab_index(&path) {
+                Some(index) => self.switch_tab(index),
+                None => {
+                    self.settings.sprites_path = path;
+                    self.reload();
+                }
+            }
+        }
     }
 
-    /// Check for any sprites that have been changed since the application started
+    /// Get the tab a sprites folder is open in.
     /// # Arguments
-    /// * `already_changed_sprites` - A list of sprites that have already been marked as changed in the application
+    /// * `sprites_path` - The path to the sprites folder
     /// # Returns
-    /// * `Vec<Sprite>` A list of all changed sprites
-    fn check_for_changed_sprites(&mut self) -> Vec<Sprite> {
-        let changed_sprites = self.state.changed_sprites.clone();
-        let mut sprites = changed_sprites
-            .iter()
-            .map(|sprite| {
-                self.get_collection(sprite.collection_name.clone())
-                    .sprites
-                    .par_iter()
-                    .find_first(|s| s.id == sprite.id)
-                    .expect("Failed to find sprite in collection")
-                    .clone()
-            })
-            .collect::<Vec<_>>();
-        sprites.retain(|sprite| !self.state.changed_sprites.contains(sprite));
-        return sprites;
+    /// * `Option<usize>` The index of the tab, if the folder is open
+    fn tab_index(&self, sprites_path: &str) -> Option<usize> {
+        (0..self.tabs.len()).find(|&index| {
+            if index == self.current_tab {
+                self.tab.loaded_path == sprites_path
+            } else {
+                self.tabs[index].loaded_path == sprites_path
+            }
+        })
+    }
+
+    /// Open a sprites folder in a new tab, or switch to the tab it is already open in.
+    /// # Arguments
+    /// * `sprites_path` - The path to the sprites folder
+    fn open_tab(&mut self, sprites_path: String) {
+        if let Some(index) = self.tab_index(&sprites_path) {
+            self.switch_tab(index);
+            return;
+        }
+        self.tabs.push(TabState::default());
+        self.switch_tab(self.tabs.len() - 1);
+        self.settings.sprites_path = sprites_path;
+        self.reload();
+    }
+
+    /// Show another tab, keeping everything about the current one as it is.
+    /// Jobs keep running in tabs that are not shown, and what they send is handled straight away.
+    /// # Arguments
+    /// * `index` - The index of the tab
+    fn switch_tab(&mut self, index: usize) {
+        if index == self.current_tab || index >= self.tabs.len() {
+            return;
+        }
+        std::mem::swap(&mut self.tab, &mut self.tabs[self.current_tab]);
+        std::mem::swap(&mut self.tab, &mut self.tabs[index]);
+        self.current_tab = index;
+        self.settings.sprites_path = self.tab.loaded_path.clone();
+    }
+
+    /// Close a tab, stopping its jobs and its watcher. The last tab cannot be closed.
+    /// # Arguments
+    /// * `index` - The index of the tab
+    fn close_tab(&mut self, index: usize) {
+        if self.tabs.len() < 2 || index >= self.tabs.len() {
+            return;
+        }
+        if index == self.current_tab {
+            self.switch_tab(if index == 0 { 1 } else { index - 1 });
+        }
+        let tab = self.tabs.remove(index);
+        tab.jobs.cancel_all();
+        if index < self.current_tab {
+            self.current_tab -= 1;
+        }
+    }
+
+    /// Work on a tab that is not shown as though it were, e.g. to handle what its jobs sent.
+    /// # Arguments
+    /// * `index` - The index of the tab
+    /// * `f` - What to do with the tab while it stands in for the shown one
+    fn with_tab(&mut self, index: usize, f: impl FnOnce(&mut Self)) {
+        std::mem::swap(&mut self.tab, &mut self.tabs[index]);
+        let sprites_path = std::mem::replace(
+            &mut self.settings.sprites_path,
+            self.tab.loaded_path.clone(),
+        );
+        f(self);
+        self.settings.sprites_path = sprites_path;
+        std::mem::swap(&mut self.tab, &mut self.tabs[index]);
+    }
+
+    /// Load the dump in the sprites folder again from scratch, e.g. after the sprites path was changed, and watch it instead of the previous one.
+    /// Everything selected, found or recorded for the previous dump is forgotten.
+    fn reload(&mut self) {
+        self.tab.state = AppState {
+            show_backups: self.tab.state.show_backups,
+            show_history: self.tab.state.show_history,
+            show_jobs: self.tab.state.show_jobs,
+            show_project: self.tab.state.show_project,
+            ..AppState::default()
+        };
+        self.tab.frame_timer = Some(Instant::now());
+        self.tab.sprite_diff = None;
+        self.tab.original_texture = None;
+        self.tab.preview = None;
+        self.tab.history = History::default();
+        self.start_load();
+        self.start_watcher();
+        if self.tab.state.show_backups {
+            self.load_backups();
+        }
     }
 
     /// Check the frame timer and update the current frame if necessary.
     fn check_frame_timer(&mut self) {
-        if let Some(frame_timer) = self.frame_timer {
-            if frame_timer.elapsed().as_secs_f32() > 1.0 / self.state.current_clip.fps {
-                self.frame_timer = Some(Instant::now());
-                self.state.current_frame_index += 1;
-                if self.state.current_frame_index >= self.state.current_clip.frames.len() {
-                    self.state.current_frame_index = self.state.current_clip.loop_start as usize;
+        if self.tab.state.current_clip.frames.is_empty() {
+            return;
+        }
+        if let Some(frame_timer) = self.tab.frame_timer {
+            if frame_timer.elapsed().as_secs_f32() > 1.0 / self.tab.state.current_clip.fps {
+                self.tab.frame_timer = Some(Instant::now());
+                self.tab.state.current_frame_index += 1;
+                if self.tab.state.current_frame_index >= self.tab.state.current_clip.frames.len() {
+                    self.tab.state.current_frame_index =
+                        self.tab.state.current_clip.loop_start as usize;
                 }
-                self.state.current_frame =
-                    self.state.current_clip.frames[self.state.current_frame_index].clone();
+                self.tab.state.current_frame =
+                    self.tab.state.current_clip.frames[self.tab.state.current_frame_index].clone();
             }
         }
     }
 
     /// Get an animation from a collection.
     /// # Arguments
-    /// * `collection_name` - The name of the collection
-    /// * `state` - The application state
+    /// * `collection` - The collection
     /// # Returns
-    /// * `Animation` - The found animation
-    fn get_animation_from_collection_name(&self, collection: Collection) -> Animation {
-        let animation = match self
+    /// * `Option<Animation>` - The found animation
+    fn get_animation_from_collection_name(&self, collection: &Collection) -> Option<Animation> {
+        self.tab
             .state
             .loaded_animations
             .par_iter()
@@ -684,455 +1159,1561 @@ impl App {
                         }
                     })
                 })
-            }) {
-            Some(anim) => anim.clone(),
-            None => panic!(
-                "Failed to find animation from collection {:?}",
-                collection.name
-            ),
-        };
-
-        animation
+            })
+            .cloned()
     }
 
     /// Get a collection by its name.
     /// # Arguments
     /// * `collection_name` - The name of the collection
     /// # Returns
-    /// *`Collection` The collection with the given name
-    fn get_collection(&self, collection_name: String) -> Collection {
-        self.state
+    /// * `Option<Collection>` The collection with the given name
+    fn get_collection(&self, collection_name: &str) -> Option<Collection> {
+        self.tab
+            .state
             .loaded_collections
             .par_iter()
             .find_first(|cln| cln.name == collection_name)
-            .expect("Failed to find collection")
-            .clone()
+            .cloned()
     }
 
-    /// Get a collection from a sprite's name
+    /// Get the other changed duplicates of a sprite.
     /// # Arguments
-    /// * `sprite_name` - The name of the sprite
-    /// * `state` - The application state
+    /// * `sprite` - The sprite
     /// # Returns
-    /// * `Collection` - The found collection
-    fn get_collection_from_sprite_name(&self, sprite_name: String) -> Collection {
-        let collection =
-            match self
-                .state
-                .loaded_collections
-                .par_iter()
-                .find_map_first(|collection| {
-                    collection.sprites.par_iter().find_map_first(|sprite| {
-                        if sprite.name == sprite_name {
-                            Some(collection)
-                        } else {
-                            None
-                        }
-                    })
-                }) {
-                Some(collection) => collection.clone(),
-                None => panic!("Failed to find collection from sprite name {sprite_name}"),
-            };
+    /// * `Vec<Sprite>` The changed sprites with the same ID in the same collection
+    fn changed_duplicates(&self, sprite: &Sprite) -> Vec<Sprite> {
+        self.tab
+            .state
+            .changed_sprites
+            .iter()
+            .filter(|duplicate| {
+                duplicate.id == sprite.id
+                    && duplicate.collection_name == sprite.collection_name
+                    && duplicate.path != sprite.path
+            })
+            .cloned()
+            .collect()
+    }
 
-        collection
+    /// Compare two duplicate sprites pixel by pixel and show the differences in the inspector.
+    /// # Arguments
+    /// * `ctx` - The egui context
+    /// * `sprite` - The sprite to compare
+    /// * `duplicate` - The duplicate to compare it with
+    fn compare_sprites(&mut self, ctx: &egui::Context, sprite: Sprite, duplicate: Sprite) {
+        let sprites_path = PathBuf::from(self.settings.sprites_path.clone());
+        match diff::diff_sprites(&sprites_path, &sprite, &duplicate) {
+            Ok(sprite_diff) => {
+                let image = ColorImage::from_rgba_unmultiplied(
+                    [
+                        sprite_diff.image.width() as usize,
+                        sprite_diff.image.height() as usize,
+                    ],
+                    sprite_diff.image.as_raw(),
+                );
+                let texture = ctx.load_texture("spritediff", image, TextureOptions::NEAREST);
+                self.tab.sprite_diff = Some((sprite_diff, texture));
+                self.tab.original_texture = None;
+                self.tab.frame_timer = None;
+                self.tab.state.current_frame = sprite;
+                self.tab.state.diff_sprite = duplicate;
+                self.tab.state.inspect_mode = InspectMode::Diff;
+            }
+            Err(e) => self.errors.push(e),
+        }
     }
 
-    /// Load collections and animations from sprite files on disk.
-    fn load_collections_and_animations(&mut self) {
-        let sprites_path = PathBuf::from(self.state.settings.sprites_path.clone());
-        if let Ok(anim_paths) = fs::read_dir(sprites_path.clone()) {
-            for anim_path in anim_paths {
-                if let Ok(anim_entry) = anim_path {
-                    if !anim_entry.path().is_dir() {
-                        continue;
+    /// Compare a sprite pixel by pixel with the region of the atlas it was dumped from and show the differences in the inspector.
+    /// # Arguments
+    /// * `ctx` - The egui context
+    /// * `sprite` - The sprite to compare
+    fn compare_with_atlas(&mut self, ctx: &egui::Context, sprite: Sprite) {
+        let Some(collection) = self.get_collection(&sprite.collection_name) else {
+            self.errors.push(SpritePackerError::CollectionNotFound(
+                sprite.collection_name.clone(),
+            ));
+            return;
+        };
+        let sprites_path = PathBuf::from(self.settings.sprites_path.clone());
+        match diff::diff_with_atlas(&sprites_path, &collection, &sprite) {
+            Ok((sprite_diff, original_image)) => {
+                let image = ColorImage::from_rgba_unmultiplied(
+                    [
+                        sprite_diff.image.width() as usize,
+                        sprite_diff.image.height() as usize,
+                    ],
+                    sprite_diff.image.as_raw(),
+                );
+                let texture = ctx.load_texture("spritediff", image, TextureOptions::NEAREST);
+                let original_image = original_image.image.to_rgba8();
+                let image = ColorImage::from_rgba_unmultiplied(
+                    [
+                        original_image.width() as usize,
+                        original_image.height() as usize,
+                    ],
+                    original_image.as_raw(),
+                );
+                self.tab.original_texture =
+                    Some(ctx.load_texture("originalframe", image, TextureOptions::NEAREST));
+                self.tab.sprite_diff = Some((sprite_diff, texture));
+                self.tab.frame_timer = None;
+                self.tab.state.current_frame = sprite;
+                self.tab.state.diff_sprite = Sprite::default();
+                self.tab.state.inspect_mode = InspectMode::Diff;
+            }
+            Err(e) => self.errors.push(e),
+        }
+    }
+
+    /// Show two duplicate sprites side by side along with the pixels that differ between them.
+    /// # Arguments
+    /// * `ui` - The UI to show the comparison in
+    fn show_sprite_diff(&mut self, ui: &mut egui::Ui) {
+        let Some((sprite_diff, texture)) = &self.tab.sprite_diff else {
+            return;
+        };
+        let language = self.settings.language.clone();
+        let duplicates = self.changed_duplicates(&self.tab.state.current_frame);
+        let mut compare_with = None;
+        let mut compare_with_atlas = false;
+        ui.horizontal(|ui| {
+            ui.vertical(|ui| {
+                ui.label(self.tab.state.current_frame.name.clone());
+                let frame_image =
+                    egui::Image::new(self.frame_url(&self.tab.state.current_frame.path))
+                        .fit_to_exact_size(Vec2::new(128., 128.))
+                        .maintain_aspect_ratio(true);
+                ui.add(frame_image);
+            });
+            ui.vertical(|ui| {
+                if let Some(original_texture) = &self.tab.original_texture {
+                    ui.label(translate("Original", language.clone()));
+                    let original_image =
+                        egui::Image::new(SizedTexture::from_handle(original_texture))
+                            .fit_to_exact_size(Vec2::new(128., 128.))
+                            .maintain_aspect_ratio(true);
+                    ui.add(original_image);
+                } else {
+                    ui.label(self.tab.state.diff_sprite.name.clone());
+                    let frame_image =
+                        egui::Image::new(self.frame_url(&self.tab.state.diff_sprite.path))
+                            .fit_to_exact_size(Vec2::new(128., 128.))
+                            .maintain_aspect_ratio(true);
+                    ui.add(frame_image);
+                }
+            });
+            ui.vertical(|ui| {
+                ui.label(translate("Differences", language.clone()));
+                let diff_image = egui::Image::new(SizedTexture::from_handle(texture))
+                    .fit_to_exact_size(Vec2::new(128., 128.))
+                    .maintain_aspect_ratio(true);
+                ui.add(diff_image);
+            });
+        });
+        ui.label(format!(
+            "{}: {}",
+            translate("Different Pixels", language.clone()),
+            sprite_diff.different_pixels
+        ));
+        if let Some(bounds) = sprite_diff.bounds {
+            ui.label(format!(
+                "{}: {}, {} {}x{}",
+                translate("Bounds", language.clone()),
+                bounds.x,
+                bounds.y,
+                bounds.width,
+                bounds.height
+            ));
+        }
+        let selected_text = if self.tab.original_texture.is_some() {
+            translate("Original", language.clone()).to_string()
+        } else {
+            self.tab.state.diff_sprite.name.clone()
+        };
+        egui::ComboBox::new("diffselect", translate("Compare", language.clone()))
+            .selected_text(selected_text)
+            .show_ui(ui, |ui| {
+                let selected = self.tab.original_texture.is_some();
+                if ui
+                    .selectable_label(selected, translate("Original", language.clone()))
+                    .clicked()
+                    && !selected
+                {
+                    compare_with_atlas = true;
+                }
+                for duplicate in &duplicates {
+                    let selected = *duplicate == self.tab.state.diff_sprite;
+                    if ui
+                        .selectable_label(selected, duplicate.name.clone())
+                        .clicked()
+                        && !selected
+                    {
+                        compare_with = Some(duplicate.clone());
+                    }
+                }
+            });
+
+        if let Some(duplicate) = compare_with {
+            self.compare_sprites(ui.ctx(), self.tab.state.current_frame.clone(), duplicate);
+        } else if compare_with_atlas {
+            self.compare_with_atlas(ui.ctx(), self.tab.state.current_frame.clone());
+        }
+    }
+
+    /// Get the URL of a frame image for previewing it.
+    /// # Arguments
+    /// * `frame_path` - The path of the frame as stored in the dump
+    /// # Returns
+    /// * `String` The URL of the frame image
+    fn frame_url(&self, frame_path: &str) -> String {
+        if Path::new(frame_path).exists() {
+            format!("file://{frame_path}")
+        } else {
+            format!(
+                "file://{}/{}",
+                self.settings.sprites_path.clone(),
+                frame_path
+            )
+        }
+    }
+
+    /// Get the URL of the atlas of a collection for previewing it.
+    /// # Arguments
+    /// * `collection` - The collection
+    /// # Returns
+    /// * `String` The URL of the atlas
+    fn atlas_url(&self, collection: &Collection) -> String {
+        if collection.path.exists() {
+            format!("file://{}", collection.path.display())
+        } else {
+            format!(
+                "file://{}/{}",
+                self.settings.sprites_path.clone(),
+                collection.path.display()
+            )
+        }
+    }
+
+    /// Get the name to list a frame under, noting whether it was modified or deleted since the last check.
+    /// # Arguments
+    /// * `sprite` - The frame
+    /// # Returns
+    /// * `Option<String>` The name with its note, or `None` if the frame has not changed on disk
+    fn modified_label(&self, sprite: &Sprite) -> Option<String> {
+        self.tab
+            .state
+            .modified_sprites
+            .iter()
+            .find(|(modified_sprite, _)| modified_sprite == sprite)
+            .map(|(_, label)| label.clone())
+    }
+
+    /// Select a changed sprite along with its collection, animation and clip.
+    /// # Arguments
+    /// * `sprite` - The changed sprite
+    fn select_changed_sprite(&mut self, sprite: &Sprite) {
+        let Some(collection) = self.get_collection(&sprite.collection_name) else {
+            self.errors.push(SpritePackerError::CollectionNotFound(
+                sprite.collection_name.clone(),
+            ));
+            return;
+        };
+        self.tab.state.current_collection = collection.clone();
+        let Some(animation) = self.get_animation_from_collection_name(&collection) else {
+            return;
+        };
+        self.tab.state.current_animation = animation.clone();
+        let Some(clip) = animation.clips.par_iter().find_first(|clip| {
+            clip.frames
+                .par_iter()
+                .find_first(|frame| frame.name == sprite.name)
+                .is_some()
+        }) else {
+            return;
+        };
+        self.tab.state.current_clip = clip.clone();
+        if let Some(frame) = clip
+            .frames
+            .par_iter()
+            .find_first(|frame| frame.name == sprite.name)
+        {
+            self.tab.state.current_frame = frame.clone();
+        }
+    }
+
+    /// Show the options of the dump stored in its project file, saving them as they are changed.
+    /// # Arguments
+    /// * `ctx` - The egui context
+    fn show_project(&mut self, ctx: &egui::Context) {
+        let language = self.settings.language.clone();
+        let mut is_open = true;
+        let mut is_changed = false;
+        let default_layout = self.layout();
+        egui::Window::new(translate("Project", language.clone()))
+            .open(&mut is_open)
+            .default_width(400.)
+            .show(ctx, |ui| {
+                ui.weak(PROJECT_FILE_NAME);
+                ui.separator();
+
+                let mut overrides_output_dir = self.tab.project.output_dir.is_some();
+                ui.horizontal(|ui| {
+                    if ui
+                        .checkbox(
+                            &mut overrides_output_dir,
+                            translate("Output Directory", language.clone()),
+                        )
+                        .changed()
+                    {
+                        self.tab.project.output_dir = overrides_output_dir
+                            .then(|| PathBuf::from(self.settings.output_dir.clone()));
+                        is_changed = true;
+                    }
+                    if let Some(output_dir) = self.tab.project.output_dir.as_mut() {
+                        let mut text = output_dir.display().to_string();
+                        let response = ui.text_edit_singleline(&mut text);
+                        if response.changed() {
+                            *output_dir = PathBuf::from(text);
+                        }
+                        is_changed |= response.lost_focus();
                     }
-                    let sprite_info_path =
-                        anim_entry.path().join("0.Atlases").join("SpriteInfo.json");
-                    if let Ok(sprite_info_text) = fs::read_to_string(sprite_info_path) {
-                        let sprite_info: SpriteInfo = serde_json::from_str(&sprite_info_text)
-                            .expect("Failed to parse SpriteInfo.json");
-                        for i in 0..sprite_info.id.len() {
-                            if let Some(sprite) = sprite_info.at(i) {
-                                if !PathBuf::from(sprite.path.clone()).exists()
-                                    && !sprites_path.join(sprite.path.clone()).exists()
-                                {
-                                    continue;
+                });
+
+                let mut overrides_layout = self.tab.project.layout.is_some();
+                if ui
+                    .checkbox(&mut overrides_layout, translate("Layout", language.clone()))
+                    .changed()
+                {
+                    self.tab.project.layout = overrides_layout.then_some(default_layout);
+                    is_changed = true;
+                }
+                if let Some(layout) = self.tab.project.layout.as_mut() {
+                    ui.horizontal(|ui| {
+                        ui.label(translate("New Layout", language.clone()));
+                        let mut repack = matches!(layout, Layout::Repack { .. });
+                        if ui.add(switch(&mut repack)).changed() {
+                            *layout = if repack {
+                                Layout::Repack {
+                                    max_size: self.settings.max_atlas_size,
+                                    padding: self.settings.padding,
+                                    retrim: self.settings.retrim,
                                 }
+                            } else {
+                                Layout::Original
+                            };
+                            is_changed = true;
+                        }
+                        if let Layout::Repack {
+                            max_size,
+                            padding,
+                            retrim,
+                        } = layout
+                        {
+                            ui.label(translate("Max Atlas Size", language.clone()));
+                            let response = ui.add(DragValue::new(max_size).clamp_range(1..=16384));
+                            is_changed |= response.changed();
+                            ui.label(translate("Padding", language.clone()));
+                            let response = ui.add(DragValue::new(padding).clamp_range(0..=64));
+                            is_changed |= response.changed();
+                            ui.label(translate("Re-trim", language.clone()));
+                            is_changed |= ui.add(switch(retrim)).changed();
+                        }
+                    });
+                }
+                ui.separator();
 
-                                if let Some(collection) = self
-                                    .state
-                                    .loaded_collections
-                                    .iter()
-                                    .find(|cln| cln.name == sprite.collection_name)
-                                {
-                                    let mut collection = collection.clone();
-                                    collection.sprites.push(sprite);
-                                    self.state
-                                        .loaded_collections
-                                        .retain(|cln| cln.name != collection.name);
-                                    self.state.loaded_collections.push(collection);
+                ui.label(translate("Ignored Collections", language.clone()));
+                ScrollArea::new(Vec2b::new(false, true))
+                    .id_source("ignoredcollections")
+                    .max_height(200.)
+                    .show(ui, |ui| {
+                        for collection in &self.tab.state.loaded_collections {
+                            let mut is_ignored = self.tab.project.is_ignored(&collection.name);
+                            if ui.checkbox(&mut is_ignored, &collection.name).changed() {
+                                if is_ignored {
+                                    self.tab
+                                        .project
+                                        .ignored_collections
+                                        .push(collection.name.clone());
                                 } else {
-                                    let collection_name = sprite.clone().collection_name;
-                                    let mut cln = Collection {
-                                        name: collection_name.clone(),
-                                        path: anim_entry
-                                            .path()
-                                            .join("0.Atlases")
-                                            .join(format!("{}.png", collection_name)),
-                                        sprites: vec![],
-                                    };
-                                    cln.sprites.push(sprite);
-                                    self.state.loaded_collections.push(cln);
+                                    self.tab
+                                        .project
+                                        .ignored_collections
+                                        .retain(|name| *name != collection.name);
                                 }
+                                is_changed = true;
                             }
                         }
+                    });
+                ui.separator();
 
-                        let mut clips = vec![];
-                        if let Ok(clip_paths) = fs::read_dir(anim_entry.path()) {
-                            for clip_path in clip_paths {
-                                if let Ok(clip_entry) = clip_path {
-                                    if !clip_entry.path().is_dir()
-                                        || clip_entry
-                                            .path()
-                                            .file_name()
-                                            .expect("Failed to get file name of clip entry")
-                                            == "0.Atlases"
-                                    {
-                                        continue;
-                                    }
-                                    let mut frames = vec![];
-                                    let mut fps = 12.;
-                                    let mut loop_start = 0;
-                                    if let Ok(frame_paths) = fs::read_dir(clip_entry.path()) {
-                                        for frame_path in frame_paths {
-                                            if let Ok(frame_entry) = frame_path {
-                                                if frame_entry.file_name() == "AnimInfo.json" {
-                                                    if let Ok(anim_info_text) =
-                                                        fs::read_to_string(frame_entry.path())
-                                                    {
-                                                        let anim_info: AnimInfo =
-                                                            match serde_json::from_str(
-                                                                &anim_info_text,
-                                                            ) {
-                                                                Ok(anim_info) => anim_info,
-                                                                Err(_) => AnimInfo {
-                                                                    fps: 12.0,
-                                                                    loop_start: 0,
-                                                                    num_frames: 0,
-                                                                    collection_name: "".to_string(),
-                                                                },
-                                                            };
-                                                        fps = anim_info.fps;
-                                                        loop_start = anim_info.loop_start;
-                                                    }
-                                                    continue;
-                                                } else if frame_entry
-                                                    .path()
-                                                    .extension()
-                                                    .expect("Failed to get extension of frame path")
-                                                    != "png"
-                                                {
-                                                    continue;
-                                                }
-
-                                                let index = sprite_info
-                                                    .path
-                                                    .par_iter()
-                                                    .position_first(|path| {
-                                                        let stripped_path = path.replace("./", "/").replace(".\\", "\\");
-                                                        frame_entry.path().ends_with(stripped_path)
-                                                    })
-                                                    .expect(
-                                                        format!(
-                                                            "Failed to find sprite for frame at {:?}",
-                                                            frame_entry.path()
-                                                        )
-                                                        .as_str(),
-                                                    );
-
-                                                let sprite = sprite_info.at(index).expect(
-                                                    format!(
-                                                        "Failed to get sprite at index {index}"
-                                                    )
-                                                    .as_str(),
-                                                );
-
-                                                frames.push(sprite);
-                                            }
-                                        }
-                                    }
-
-                                    if let Some(clip_name) = clip_entry.file_name().to_str() {
-                                        clips.push(Clip::new(
-                                            clip_name.to_string(),
-                                            frames,
-                                            fps,
-                                            loop_start,
-                                        ));
-                                    }
+                ui.label(translate("Resolutions", language.clone()));
+                let mut removed = None;
+                ScrollArea::new(Vec2b::new(false, true))
+                    .id_source("resolutions")
+                    .max_height(200.)
+                    .show(ui, |ui| {
+                        for (index, resolution) in self.tab.project.resolutions.iter().enumerate() {
+                            ui.horizontal(|ui| {
+                                if ui.small_button("✖").clicked() {
+                                    removed = Some(index);
                                 }
-                            }
+                                ui.label(format!(
+                                    "{} {}: {}",
+                                    resolution.collection_name, resolution.id, resolution.path
+                                ));
+                            });
                         }
+                    });
+                if let Some(index) = removed {
+                    self.tab.project.resolutions.remove(index);
+                    is_changed = true;
+                }
+            });
+        self.tab.state.show_project = is_open;
 
-                        let anim_file = anim_entry.file_name();
-                        let anim_name = anim_file
-                            .to_str()
-                            .expect("Failed to get animation name from file name");
+        if is_changed {
+            self.save_project();
+        }
+    }
 
-                        self.state.loaded_animations.push(Animation {
-                            name: anim_name.to_string(),
-                            clips: clips.to_vec(),
-                        });
-                    }
-                }
-            }
+    /// Save the project file of the dump.
+    fn save_project(&mut self) {
+        let sprites_path = PathBuf::from(self.settings.sprites_path.clone());
+        if let Err(e) = self.tab.project.save(&sprites_path) {
+            self.errors.push(e);
         }
+    }
 
-        if self.state.loaded_animations.len() > 0 {
-            self.state.current_animation = self.state.loaded_animations[0].clone();
-            self.state.current_clip = self.state.current_animation.clips[0].clone();
-            self.state.current_frame = self.state.current_clip.frames[0].clone();
+    /// Load the collections and animations of the sprites folder in the background.
+    /// Animations are shown as soon as they are loaded, so they can be browsed before the whole dump has been read.
+    fn start_load(&mut self) {
+        if let Some(id) = self.tab.load_job.take() {
+            self.tab.jobs.cancel(id);
+        }
+        self.tab.state.loaded_collections = vec![];
+        self.tab.state.loaded_animations = vec![];
+        self.tab.state.can_pack = false;
+        self.tab.loaded_path = self.settings.sprites_path.clone();
+        let sprites_path = self.settings.sprites_path.clone();
+        self.settings.add_recent_path(&sprites_path);
+        let sprites_path = PathBuf::from(self.settings.sprites_path.clone());
+        if self.tab.hash_cache.path != sprites_path.join(CACHE_FILE_NAME) {
+            self.tab.hash_cache = Arc::new(HashCache::open(&sprites_path));
         }
+        self.tab.project = Project::open(&sprites_path).unwrap_or_else(|e| {
+            self.errors.push(e);
+            Project::default()
+        });
+
+        let id = self
+            .tab
+            .jobs
+            .spawn(JobKind::Load, "Load".to_string(), move |context| {
+                load::load_incrementally(
+                    &sprites_path,
+                    Some(&context.cancel),
+                    |animation_dump, progress| {
+                        context.send(JobMessage::Loaded(animation_dump));
+                        context.progress(progress);
+                    },
+                )?;
+                Ok(JobResult::Loaded)
+            });
+        self.tab.load_job = Some(id);
     }
 
-    /// Replace all duplicate sprites in a collection.
+    /// Add an animation folder loaded in the background to the loaded collections and animations.
     /// # Arguments
-    /// * `source_sprite` - The sprite to replace duplicates with
-    fn replace_duplicate_sprites(&mut self, source_sprite: Sprite) {
-        let sprites_path = PathBuf::from(self.state.settings.sprites_path.clone());
-
-        self.watcher
-            .as_mut()
-            .expect("Watcher is none")
-            .unwatch(&sprites_path.clone())
-            .expect("Failed to unwatch sprites path");
-
-        let source_path = if sprites_path.join(source_sprite.path.clone()).exists() {
-            sprites_path.join(source_sprite.path.clone())
-        } else if PathBuf::from(source_sprite.path.clone()).exists() {
-            PathBuf::from(source_sprite.path.clone())
-        } else {
-            panic!(
-                "Failed to get a valid path from source sprite at {}",
-                source_sprite.path
-            );
+    /// * `animation_dump` - The animation, with the sprites it adds to each collection
+    fn add_loaded_animation(&mut self, animation_dump: Dump) {
+        let mut dump = Dump {
+            collections: std::mem::take(&mut self.tab.state.loaded_collections),
+            animations: std::mem::take(&mut self.tab.state.loaded_animations),
+            errors: vec![],
         };
+        dump.extend(animation_dump);
+        self.tab.state.loaded_collections = dump.collections;
+        self.tab.state.loaded_animations = dump.animations;
+        self.errors.extend(dump.errors);
 
-        let source_image = match image::open(source_path.clone()) {
-            Ok(image) => image,
-            Err(e) => panic!(
-                "Failed to open image at path {:?}: {}",
-                source_path.display(),
-                e
-            ),
-        };
-
-        let source_image = SpriteImage {
-            sprite: source_sprite.clone(),
-            image: source_image,
-        };
+        if self.tab.state.current_animation != Animation::default() {
+            return;
+        }
+        if let Some(animation) = self.tab.state.loaded_animations.first() {
+            self.tab.state.current_animation = animation.clone();
+            self.tab.state.current_clip = animation.clips.first().cloned().unwrap_or_default();
+            self.tab.state.current_frame = self
+                .tab
+                .state
+                .current_clip
+                .frames
+                .first()
+                .cloned()
+                .unwrap_or_default();
+        }
+    }
 
-        let collection = self.get_collection(source_sprite.collection_name.clone());
-        for sprite in collection.sprites {
-            if sprite.id != source_sprite.id {
-                continue;
-            }
+    /// Replace the duplicates of one or more sprites, recording it as a single operation in the history.
+    /// # Arguments
+    /// * `source_sprites` - The sprites to replace duplicates with
+    /// * `operation_name` - The name to show for the operation in the history and backups
+    fn replace_duplicate_sprites(&mut self, source_sprites: Vec<Sprite>, operation_name: String) {
+        let sprites_path = PathBuf::from(self.settings.sprites_path.clone());
+        let collections = self.tab.state.loaded_collections.clone();
+        let paths = source_sprites
+            .iter()
+            .flat_map(|source_sprite| {
+                collections
+                    .iter()
+                    .filter(|collection| collection.name == source_sprite.collection_name)
+                    .flat_map(|collection| {
+                        replace::written_paths(&sprites_path, collection, source_sprite)
+                    })
+            })
+            .collect();
+        self.spawn_writing_job(
+            JobKind::Replace,
+            operation_name.clone(),
+            paths,
+            move |context| {
+                let backup = Backup::new(&sprites_path, &operation_name);
+                let mut changes = vec![];
+                let mut replaced_sprites = vec![];
+                for (index, source_sprite) in source_sprites.iter().enumerate() {
+                    // Stop between sprites rather than failing, so that what was already replaced can still be undone
+                    if context.cancel.is_cancelled() {
+                        break;
+                    }
+                    let Some(collection) = collections
+                        .iter()
+                        .find(|collection| collection.name == source_sprite.collection_name)
+                    else {
+                        context.error(SpritePackerError::CollectionNotFound(
+                            source_sprite.collection_name.clone(),
+                        ));
+                        continue;
+                    };
+                    // Frames replaced before an error are still recorded, so they can be undone
+                    match replace::replace_duplicate_sprites(
+                        &sprites_path,
+                        collection,
+                        source_sprite,
+                        Some(&backup),
+                        &mut changes,
+                    ) {
+                        Ok(()) => replaced_sprites.push(source_sprite.clone()),
+                        Err(e) => context.error(e),
+                    }
+                    context.progress((index + 1) as f32 / source_sprites.len() as f32);
+                }
+                Ok(JobResult::Replaced {
+                    operation: Operation {
+                        name: operation_name,
+                        changes,
+                    },
+                    source_sprites: replaced_sprites,
+                })
+            },
+        );
+    }
 
-            let sprite_path = if sprites_path.join(sprite.path.clone()).exists() {
-                sprites_path.join(sprite.path.clone())
-            } else if PathBuf::from(sprite.path.clone()).exists() {
-                PathBuf::from(sprite.path.clone())
-            } else {
-                panic!("Failed to get a valid path from sprite at {}", sprite.path);
-            };
+    /// Replace the duplicates of one or more sprites, or preview what would be replaced if previewing is turned on.
+    /// # Arguments
+    /// * `source_sprites` - The sprites to replace duplicates with
+    /// * `operation_name` - The name to show for the operation in the history and backups
+    fn replace_or_preview(&mut self, source_sprites: Vec<Sprite>, operation_name: String) {
+        if self.settings.preview_changes {
+            self.preview_replace(source_sprites, operation_name);
+        } else {
+            self.replace_duplicate_sprites(source_sprites, operation_name);
+        }
+    }
 
-            let sprite_image = image::open(sprite_path.clone()).expect(
-                format!("Failed to open image at path {:?}", sprite_path.display()).as_str(),
-            );
+    /// Choose in the background which duplicate of each changed sprite to keep, using the selected strategy.
+    /// # Arguments
+    /// * `changed_sprites` - The sprites to choose duplicates for
+    /// * `operation_name` - The name of the operation to replace the duplicates in once they are chosen,
+    ///   or `None` to only select the chosen duplicate so that it can be inspected before replacing
+    fn choose_sources(&mut self, changed_sprites: Vec<Sprite>, operation_name: Option<String>) {
+        let sprites_path = PathBuf::from(self.settings.sprites_path.clone());
+        let collections = self.tab.state.loaded_collections.clone();
+        let strategy = self.settings.resolve_strategy;
+        let project = self.tab.project.clone();
+        let hash_cache = self.tab.hash_cache.clone();
+        let name = operation_name
+            .clone()
+            .unwrap_or_else(|| "Resolve".to_string());
+        self.tab.jobs.spawn(JobKind::Resolve, name, move |context| {
+            let conflicts = resolve::choose_sources(
+                &sprites_path,
+                &collections,
+                &changed_sprites,
+                strategy,
+                Some(&hash_cache),
+                Some(&project),
+                Some(&context.cancel),
+            )?;
+            if let Err(e) = hash_cache.save() {
+                context.error(e);
+            }
+            let mut source_sprites = vec![];
+            for conflict in conflicts {
+                match conflict.source {
+                    Some(source_sprite) => source_sprites.push(source_sprite),
+                    None => {
+                        let names = conflict
+                            .duplicates
+                            .iter()
+                            .map(|sprite| sprite.name.clone())
+                            .collect::<Vec<_>>();
+                        context.error(SpritePackerError::AmbiguousDuplicates(names.join(", ")));
+                    }
+                }
+            }
+            Ok(JobResult::Resolved {
+                source_sprites,
+                operation_name,
+            })
+        });
+    }
 
-            let mut sprite_image = SpriteImage {
-                sprite: sprite.clone(),
-                image: sprite_image,
-            };
+    /// Show the operations that can be undone and redone.
+    /// # Arguments
+    /// * `ui` - The UI to show the history in
+    fn show_history(&mut self, ui: &mut egui::Ui) {
+        let language = self.settings.language.clone();
+        ui.heading(translate("History", language.clone()));
+        ui.separator();
+        ui.horizontal(|ui| {
+            let button = Button::new(translate("Undo", language.clone()));
+            if ui
+                .add_enabled(
+                    self.ui_enabled() && !self.tab.history.undo_stack.is_empty(),
+                    button,
+                )
+                .on_hover_text(ui.ctx().format_shortcut(&UNDO_SHORTCUT))
+                .clicked()
+            {
+                self.undo();
+            }
+            let button = Button::new(translate("Redo", language.clone()));
+            if ui
+                .add_enabled(
+                    self.ui_enabled() && !self.tab.history.redo_stack.is_empty(),
+                    button,
+                )
+                .on_hover_text(ui.ctx().format_shortcut(&REDO_SHORTCUTS[1]))
+                .clicked()
+            {
+                self.redo();
+            }
+        });
+        ui.separator();
+        ScrollArea::new(Vec2b::new(false, true)).show(ui, |ui| {
+            for operation in &self.tab.history.undo_stack {
+                ui.label(operation.name.clone());
+            }
+            for operation in self.tab.history.redo_stack.iter().rev() {
+                ui.weak(operation.name.clone());
+            }
+        });
+    }
 
-            App::replace_sprite(source_image.clone(), &mut sprite_image);
-
-            match sprite_image.image.save(sprite_path.clone()) {
-                Ok(_) => println!(
-                    "Replaced sprite at path {:?} with sprite at path {:?}",
-                    sprite_path.display(),
-                    source_path.display()
-                ),
-                Err(e) => panic!(
-                    "Failed to save image at path {:?}: {}",
-                    sprite_path.display(),
-                    e
-                ),
+    /// Show the jobs running in the background and how the finished ones ended.
+    /// # Arguments
+    /// * `ui` - The UI to show the jobs in
+    fn show_jobs(&mut self, ui: &mut egui::Ui) {
+        let language = self.settings.language.clone();
+        ui.heading(translate("Jobs", language.clone()));
+        ui.separator();
+        let button = Button::new(translate("Clear Finished", language.clone()));
+        if ui
+            .add_enabled(
+                self.tab.jobs.jobs.iter().any(|job| !job.is_running()),
+                button,
+            )
+            .clicked()
+        {
+            self.tab.jobs.clear_finished();
+        }
+        ui.separator();
+        let mut cancelled_job = None;
+        ScrollArea::new(Vec2b::new(false, true)).show(ui, |ui| {
+            for job in self.tab.jobs.jobs.iter().rev() {
+                ui.label(job.name.clone());
+                match &job.status {
+                    JobStatus::Running => {
+                        ui.horizontal(|ui| {
+                            match job.progress {
+                                Some(progress) => {
+                                    ui.add(
+                                        ProgressBar::new(progress)
+                                            .desired_width(100.)
+                                            .show_percentage(),
+                                    );
+                                }
+                                None => {
+                                    ui.spinner();
+                                }
+                            }
+                            let button = Button::new(translate("Cancel", language.clone()));
+                            if ui.add(button).clicked() {
+                                cancelled_job = Some(job.id);
+                            }
+                        });
+                    }
+                    JobStatus::Succeeded => {
+                        ui.weak(format!(
+                            "{} ({:.1}s)",
+                            translate("Done", language.clone()),
+                            job.elapsed().as_secs_f32()
+                        ));
+                    }
+                    JobStatus::Cancelled => {
+                        ui.weak(translate("Cancelled", language.clone()));
+                    }
+                    JobStatus::Failed(error) => {
+                        ui.colored_label(Color32::RED, error);
+                    }
+                }
+                if job.errors > 0 {
+                    ui.colored_label(
+                        Color32::RED,
+                        format!("{}: {}", translate("Errors", language.clone()), job.errors),
+                    );
+                }
+                ui.separator();
             }
+        });
+        if let Some(id) = cancelled_job {
+            self.tab.jobs.cancel(id);
         }
+    }
 
-        self.state
-            .changed_sprites
-            .retain(|sprite| sprite.id != source_sprite.id);
+    /// Undo or redo the most recent operation when its keyboard shortcut is pressed.
+    /// # Arguments
+    /// * `ctx` - The egui context
+    fn handle_shortcuts(&mut self, ctx: &egui::Context) {
+        // Leave the shortcuts to text fields that are being edited
+        if !self.ui_enabled() || ctx.memory(|memory| memory.focus().is_some()) {
+            return;
+        }
+        // Redo is checked first, since the undo shortcut also matches when shift is held
+        let redo = ctx.input_mut(|input| {
+            REDO_SHORTCUTS
+                .iter()
+                .any(|shortcut| input.consume_shortcut(shortcut))
+        });
+        if redo {
+            self.redo();
+        } else if ctx.input_mut(|input| input.consume_shortcut(&UNDO_SHORTCUT)) {
+            self.undo();
+        }
+    }
 
-        self.watcher
-            .as_mut()
-            .expect("Failed to unwrap watcher")
-            .watch(&Path::new(&sprites_path), RecursiveMode::Recursive)
-            .expect("Failed to watch sprites path");
+    /// Undo the most recent operation, writing the files it changed back to how they were before it.
+    fn undo(&mut self) {
+        let paths = self
+            .tab
+            .history
+            .undo_stack
+            .last()
+            .map(Operation::paths)
+            .unwrap_or_default();
+        self.write_files(&paths, |app| match app.tab.history.undo() {
+            // The undone files may differ from their duplicates again, so they must be checked before packing
+            Ok(Some(operation)) => {
+                for change in &operation.changes {
+                    app.tab.hash_cache.invalidate(&change.path);
+                }
+                app.tab.state.can_pack = false;
+            }
+            Ok(None) => {}
+            Err(e) => app.errors.push(e),
+        });
     }
 
-    /// Replace a sprite with another sprite.
-    /// # Arguments
-    /// * `source_image` - The sprite to replace with
-    /// * `target_image` - The sprite to replace
-    fn replace_sprite(source_image: SpriteImage, target_image: &mut SpriteImage) {
-        let target_ptr = Arc::new(Mutex::new(target_image));
-        let sub_image = source_image.trim();
-        (0..sub_image.width()).into_par_iter().for_each(|x| {
-            (0..sub_image.height()).into_par_iter().for_each(|y| {
-                let source_y = sub_image.height() - y - 1;
-                let pixel = sub_image.get_pixel(x, source_y);
-                let mut target_image = target_ptr.lock().unwrap();
-                let target_x = x + target_image.sprite.xr as u32;
-                let target_y =
-                    target_image.image.height() - (y + target_image.sprite.yr as u32) - 1;
-                target_image.image.put_pixel(target_x, target_y, pixel);
-            });
+    /// Redo the most recently undone operation, writing the files it changed back to how they were after it.
+    fn redo(&mut self) {
+        let paths = self
+            .tab
+            .history
+            .redo_stack
+            .last()
+            .map(Operation::paths)
+            .unwrap_or_default();
+        self.write_files(&paths, |app| match app.tab.history.redo() {
+            Ok(Some(operation)) => {
+                for change in &operation.changes {
+                    app.tab.hash_cache.invalidate(&change.path);
+                }
+                app.tab.state.can_pack = false;
+            }
+            Ok(None) => {}
+            Err(e) => app.errors.push(e),
         });
     }
 
-    /// Pack a collection of sprites into an atlas.
+    /// Show the backups of the current dump in a window, with buttons for restoring them.
     /// # Arguments
-    /// * `collection` - The collection to pack
-    /// * `sprites_path` - The path to the sprites folder
-    /// * `tx` - The channel to send progress updates through
-    fn pack_collection(collection: Collection, sprites_path: String, tx: Sender<f32>) {
-        let atlas = image::open(collection.path.clone()).expect("Failed to open atlas file");
-        let sprite_num_ptr = Arc::new(Mutex::new(0 as usize));
-        let atlas_width = atlas.width() as i32;
-        let atlas_height = atlas.height() as i32;
-        let gen_atlas = Mutex::new(atlas);
-        collection.sprites.par_iter().for_each(|sprite| {
-            let frame_path = PathBuf::from_str(sprites_path.as_str())
-                .expect("Failed to create frame path from string")
-                .join(sprite.path.clone());
-            let frame_image = image::open(frame_path.clone()).expect(
-                format!("Failed to open frame image at {:?}", frame_path.display()).as_str(),
-            );
-
-            (0..frame_image.width()).into_par_iter().for_each(|i| {
-                (0..frame_image.height()).into_par_iter().for_each(|j| {
-                    let i = i as i32;
-                    let j = j as i32;
-                    let x = if sprite.flipped {
-                        sprite.x + j - sprite.yr
-                    } else {
-                        sprite.x + i - sprite.xr
-                    };
-                    let y = if sprite.flipped {
-                        atlas_height - (sprite.y + i) - 1 + sprite.xr
-                    } else {
-                        atlas_height - (sprite.y + j) - 1 + sprite.yr
-                    };
-                    if i >= sprite.xr
-                        && i < (sprite.xr + sprite.width)
-                        && j >= sprite.yr
-                        && j < (sprite.yr + sprite.height)
-                        && x >= 0
-                        && x < atlas_width as i32
-                        && y >= 0
-                        && y < atlas_height as i32
-                    {
-                        let mut atlas = gen_atlas.lock().unwrap();
-                        atlas.put_pixel(
-                            x as u32,
-                            y as u32,
-                            frame_image
-                                .get_pixel(i as u32, (frame_image.height() as i32 - j - 1) as u32),
+    /// * `ctx` - The egui context
+    fn show_backups(&mut self, ctx: &egui::Context) {
+        let language = self.settings.language.clone();
+        let mut is_open = true;
+        let mut refresh = false;
+        let mut restore = None;
+        egui::Window::new(translate("Backups", language.clone()))
+            .open(&mut is_open)
+            .default_width(400.)
+            .show(ctx, |ui| {
+                if ui.button(translate("Refresh", language.clone())).clicked() {
+                    refresh = true;
+                }
+                ui.separator();
+                ScrollArea::new(Vec2b::new(false, true)).show(ui, |ui| {
+                    for (index, backup) in self.tab.backups.iter().enumerate() {
+                        let title = format!(
+                            "{} - {}",
+                            backup::format_timestamp(backup.timestamp),
+                            backup.operation
                         );
+                        egui::CollapsingHeader::new(title)
+                            .id_source(&backup.path)
+                            .show(ui, |ui| {
+                                let button =
+                                    Button::new(translate("Restore All", language.clone()));
+                                if ui.add_enabled(self.ui_enabled(), button).clicked() {
+                                    restore = Some((index, None));
+                                }
+                                for file in backup.files() {
+                                    ui.horizontal(|ui| {
+                                        let button =
+                                            Button::new(translate("Restore", language.clone()));
+                                        if ui.add_enabled(self.ui_enabled(), button).clicked() {
+                                            restore = Some((index, Some(file.clone())));
+                                        }
+                                        ui.label(file.original_path.display().to_string());
+                                    });
+                                }
+                            });
                     }
                 });
             });
+        self.tab.state.show_backups = is_open;
 
-            let sprite_num_ptr_clone = sprite_num_ptr.clone();
-            let mut num = loop {
-                match sprite_num_ptr_clone.try_lock() {
-                    Ok(num) => break num,
-                    Err(_) => {}
-                }
+        if let Some((index, file)) = restore {
+            self.restore_backup(index, file);
+        } else if refresh {
+            self.load_backups();
+        }
+    }
+
+    /// Load the backups of the current dump from disk.
+    fn load_backups(&mut self) {
+        let sprites_path = PathBuf::from(self.settings.sprites_path.clone());
+        match backup::list_backups(&sprites_path) {
+            Ok(backups) => self.tab.backups = backups,
+            Err(e) => self.errors.push(e),
+        }
+    }
+
+    /// Restore a single file or every file in a backup.
+    /// # Arguments
+    /// * `index` - The index of the backup in the list of loaded backups
+    /// * `file` - The file to restore, or `None` to restore the whole backup
+    fn restore_backup(&mut self, index: usize, file: Option<BackupFile>) {
+        let restored_files = match &file {
+            Some(file) => vec![file.clone()],
+            None => self.tab.backups[index].files(),
+        };
+        let paths = restored_files
+            .iter()
+            .map(|restored_file| restored_file.original_path.clone())
+            .collect::<Vec<_>>();
+        self.write_files(&paths, |app| {
+            let backup = &app.tab.backups[index];
+            let result = match file {
+                Some(file) => backup.restore_file(&file),
+                None => backup.restore(),
             };
-            *num += 1;
-            let progress = *num as f32 / collection.sprites.len() as f32;
-            tx.send(progress).expect("Failed to send progress value");
+            for path in &paths {
+                app.tab.hash_cache.invalidate(path);
+            }
+            if let Err(e) = result {
+                app.errors.push(e);
+            }
         });
+    }
+
+    /// Run a job in the background that writes to the sprites folder, without the watcher reporting what it writes as changes.
+    /// # Arguments
+    /// * `kind` - The kind of job
+    /// * `name` - The name to show for the job
+    /// * `paths` - The files the job may write, besides backups
+    /// * `work` - The work to do, which is given the job's context
+    fn spawn_writing_job(
+        &mut self,
+        kind: JobKind,
+        name: String,
+        paths: Vec<PathBuf>,
+        work: impl FnOnce(&JobContext) -> Result<JobResult> + Send + 'static,
+    ) {
+        self.tab.own_writes.lock().unwrap().begin(&paths);
+        let id = self.tab.jobs.spawn(kind, name, work);
+        // Handed back once the job has finished, however it ended
+        self.tab.job_writes.insert(id, paths);
+    }
 
-        let atlas_path = rfd::FileDialog::new()
-            .set_directory(&sprites_path)
-            .set_file_name(format!("{}.png", collection.name.clone()).as_str())
-            .add_filter("PNG Image", &["png"])
-            .save_file()
-            .expect("Failed to save generated atlas");
-        gen_atlas
-            .lock()
-            .unwrap()
-            .save(atlas_path)
-            .expect("Failed to save generated atlas");
-
-        drop(tx);
+    /// Write to the sprites folder right away, without the watcher reporting what is written as changes.
+    /// # Arguments
+    /// * `paths` - The files that may be written
+    /// * `write` - Writes the files
+    fn write_files(&mut self, paths: &[PathBuf], write: impl FnOnce(&mut Self)) {
+        self.tab.own_writes.lock().unwrap().begin(paths);
+        write(self);
+        self.tab.own_writes.lock().unwrap().end(paths);
+    }
+
+    /// Check every loaded collection in the background for changed, clipped and edited sprites.
+    fn start_check(&mut self) {
+        self.tab.state.is_checking = true;
+        self.tab.state.changed_sprites = vec![];
+        self.tab.state.exceeding_frames = vec![];
+        self.tab.state.edited_frames = vec![];
+        self.tab.state.modified_sprites = vec![];
+        let sprites_path = PathBuf::from(self.settings.sprites_path.clone());
+        let collections = self.unignored_collections();
+        let hash_cache = self.tab.hash_cache.clone();
+        self.tab
+            .jobs
+            .spawn(JobKind::Check, "Check".to_string(), move |context| {
+                let checked_collections = AtomicUsize::new(0);
+                let result = check::full_check(
+                    &sprites_path,
+                    &collections,
+                    Some(&hash_cache),
+                    Some(&context.cancel),
+                    |report| {
+                        for sprite in &report.changed_sprites {
+                            context.send(JobMessage::ChangedSprite(sprite.clone()));
+                        }
+                        for exceeding_frame in &report.exceeding_frames {
+                            context.send(JobMessage::ExceedingFrame(exceeding_frame.clone()));
+                        }
+                        for edited_frame in &report.edited_frames {
+                            context.send(JobMessage::EditedFrame(edited_frame.clone()));
+                        }
+                        let checked = checked_collections.fetch_add(1, Ordering::Relaxed) + 1;
+                        context.progress(checked as f32 / collections.len() as f32);
+                    },
+                );
+                // The frames decoded before a cancel or an error are still worth caching
+                if let Err(e) = hash_cache.save() {
+                    context.error(e);
+                }
+                result?;
+                Ok(JobResult::Checked)
+            });
     }
 
     /// Pack a single collection.
     /// # Arguments
     /// * `collection_name` - The name of the collection
-    /// * `app_handle` - The application handle
-    /// * `state` - The application state
     fn pack_single_collection(&mut self, collection_name: String) {
-        let collection = self.get_collection(collection_name.clone());
-        let sprites_path = self.state.settings.sprites_path.clone();
+        let Some(collection) = self.get_collection(&collection_name) else {
+            self.errors
+                .push(SpritePackerError::CollectionNotFound(collection_name));
+            self.tab.state.is_packing = false;
+            return;
+        };
+        let sprites_path = self.settings.sprites_path.clone();
+        let output_dir = self.output_dir();
+        let atlas_path = if self.settings.ask_save_path {
+            let directory = pack::atlas_output_path(&collection, output_dir.as_deref())
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_else(|| PathBuf::from(&sprites_path));
+            let Some(atlas_path) = rfd::FileDialog::new()
+                .set_directory(directory)
+                .set_file_name(format!("{}.png", collection.name.clone()).as_str())
+                .add_filter("PNG Image", &["png"])
+                .save_file()
+            else {
+                self.tab.state.is_packing = false;
+                return;
+            };
+            atlas_path
+        } else {
+            pack::atlas_output_path(&collection, output_dir.as_deref())
+        };
 
-        let (tx, rx) = mpsc::channel();
-        self.progress_sender = Some(tx.clone());
-        self.progress_receiver = Some(rx);
-        thread::spawn(move || App::pack_collection(collection, sprites_path, tx.clone()));
+        let layout = self.layout();
+        if self.settings.preview_changes {
+            self.preview_pack(collection, atlas_path, layout);
+        } else {
+            self.start_pack(collection, atlas_path, layout);
+        }
+    }
+
+    /// Pack a collection in the background and save it.
+    /// # Arguments
+    /// * `collection` - The collection to pack
+    /// * `atlas_path` - The path to save the atlas to
+    /// * `layout` - How to lay the sprites out in the atlas
+    fn start_pack(&mut self, collection: Collection, atlas_path: PathBuf, layout: Layout) {
+        let sprites_path = PathBuf::from(self.settings.sprites_path.clone());
+        let name = format!("Pack {}", collection.name);
+        let paths = pack::written_paths(&atlas_path);
+        self.spawn_writing_job(JobKind::Pack, name.clone(), paths, move |context| {
+            let backup = Backup::new(&sprites_path, &name);
+            pack::pack_and_save(
+                &collection,
+                &sprites_path,
+                &atlas_path,
+                layout,
+                Some(&backup),
+                Some(&context.cancel),
+                |progress| context.progress(progress),
+            )?;
+            Ok(JobResult::Packed)
+        });
+    }
+
+    /// Pack a collection in the background without saving it, then preview what saving it would write.
+    /// # Arguments
+    /// * `collection` - The collection to pack
+    /// * `atlas_path` - The path the atlas would be saved to
+    /// * `layout` - How to lay the sprites out in the atlas
+    fn preview_pack(&mut self, collection: Collection, atlas_path: PathBuf, layout: Layout) {
+        let sprites_path = PathBuf::from(self.settings.sprites_path.clone());
+        let name = format!("Preview Pack {}", collection.name);
+        self.tab.jobs.spawn(JobKind::Preview, name, move |context| {
+            let report = pack::dry_run_pack(
+                &collection,
+                &sprites_path,
+                &atlas_path,
+                layout,
+                Some(&context.cancel),
+                |progress| context.progress(progress),
+            )?;
+            Ok(JobResult::Previewed(Preview::Pack {
+                collection_name: collection.name.clone(),
+                layout,
+                report,
+            }))
+        });
     }
 
-    /// Poll for changed sprites.
-    fn poll_changed_sprites(&mut self) {
-        if let Some(rx) = self.sprite_receiver.as_mut() {
-            if let Ok(sprite) = rx.try_recv() {
-                if sprite == Sprite::default() {
-                    self.state.is_checking = false;
-                    self.state.can_pack = true;
-                    return;
+    /// Preview the frames that replacing the duplicates of one or more sprites would overwrite.
+    /// # Arguments
+    /// * `source_sprites` - The sprites to replace duplicates with
+    /// * `operation_name` - The name to show for the operation once it is applied
+    fn preview_replace(&mut self, source_sprites: Vec<Sprite>, operation_name: String) {
+        let sprites_path = PathBuf::from(self.settings.sprites_path.clone());
+        let collections = self.tab.state.loaded_collections.clone();
+        let name = format!("Preview {}", operation_name);
+        self.tab.jobs.spawn(JobKind::Preview, name, move |context| {
+            let mut reports = vec![];
+            for (index, source_sprite) in source_sprites.iter().enumerate() {
+                context.cancel.check()?;
+                let collection = collections
+                    .iter()
+                    .find(|collection| collection.name == source_sprite.collection_name)
+                    .ok_or_else(|| {
+                        SpritePackerError::CollectionNotFound(source_sprite.collection_name.clone())
+                    })?;
+                reports.extend(replace::dry_run_replace(
+                    &sprites_path,
+                    collection,
+                    source_sprite,
+                )?);
+                context.progress((index + 1) as f32 / source_sprites.len() as f32);
+            }
+            Ok(JobResult::Previewed(Preview::Replace {
+                source_sprites,
+                operation_name,
+                reports,
+            }))
+        });
+    }
+
+    /// Show the files that an operation would write, with buttons for performing or cancelling it.
+    /// # Arguments
+    /// * `ctx` - The egui context
+    fn show_preview(&mut self, ctx: &egui::Context) {
+        let Some(preview) = &self.tab.preview else {
+            return;
+        };
+        let language = self.settings.language.clone();
+        let mut apply = false;
+        let mut cancel = false;
+        egui::Window::new(translate("Preview", language.clone()))
+            .collapsible(false)
+            .default_width(400.)
+            .show(ctx, |ui| {
+                ScrollArea::new(Vec2b::new(false, true))
+                    .max_height(320.)
+                    .show(ui, |ui| match preview {
+                        Preview::Replace { reports, .. } => {
+                            if reports.is_empty() {
+                                ui.label(translate("No Changes", language.clone()));
+                            }
+                            for report in reports {
+                                ui.label(format!(
+                                    "{}: {} {}",
+                                    report.path.display(),
+                                    report.changed_pixels,
+                                    translate("Changed Pixels", language.clone())
+                                ));
+                            }
+                        }
+                        Preview::Pack { report, .. } => {
+                            ui.label(format!(
+                                "{} ({}x{}): {} {}",
+                                report.atlas_path.display(),
+                                report.atlas_width,
+                                report.atlas_height,
+                                report.changed_pixels,
+                                translate("Changed Pixels", language.clone())
+                            ));
+                            if let Some(sprite_info_path) = &report.sprite_info_path {
+                                ui.label(sprite_info_path.display().to_string());
+                            }
+                            ui.separator();
+                            for region in &report.regions {
+                                ui.label(format!(
+                                    "{} ({}, {} {}x{}): {} {}",
+                                    region.sprite_name,
+                                    region.rect.x,
+                                    region.rect.y,
+                                    region.rect.width,
+                                    region.rect.height,
+                                    region.changed_pixels,
+                                    translate("Changed Pixels", language.clone())
+                                ));
+                            }
+                        }
+                    });
+                ui.separator();
+                ui.horizontal(|ui| {
+                    if ui.button(translate("Apply", language.clone())).clicked() {
+                        apply = true;
+                    }
+                    if ui.button(translate("Cancel", language.clone())).clicked() {
+                        cancel = true;
+                    }
+                });
+            });
+
+        if cancel {
+            self.tab.preview = None;
+        } else if apply {
+            match self.tab.preview.take() {
+                Some(Preview::Replace {
+                    source_sprites,
+                    operation_name,
+                    ..
+                }) => self.replace_duplicate_sprites(source_sprites, operation_name),
+                Some(Preview::Pack {
+                    collection_name,
+                    layout,
+                    report,
+                }) => {
+                    let Some(collection) = self.get_collection(&collection_name) else {
+                        self.errors
+                            .push(SpritePackerError::CollectionNotFound(collection_name));
+                        return;
+                    };
+                    self.tab.state.is_packing = true;
+                    self.tab.state.is_packing_all = false;
+                    self.tab.state.pack_progress = 0.;
+                    self.start_pack(collection, report.atlas_path, layout);
                 }
-                if !self.state.changed_sprites.contains(&sprite) {
-                    self.state.changed_sprites.push(sprite);
+                None => {}
+            }
+        }
+    }
+
+    /// Check every loaded collection and pack each one whose duplicates are identical.
+    fn pack_all_collections(&mut self) {
+        self.tab.state.changed_sprites = vec![];
+        let collections = self.unignored_collections();
+        let sprites_path = PathBuf::from(self.settings.sprites_path.clone());
+        let output_dir = self.output_dir();
+        let layout = self.layout();
+        let hash_cache = self.tab.hash_cache.clone();
+        let paths = collections
+            .iter()
+            .flat_map(|collection| {
+                pack::written_paths(&pack::atlas_output_path(collection, output_dir.as_deref()))
+            })
+            .collect();
+        self.spawn_writing_job(
+            JobKind::Pack,
+            "Pack All".to_string(),
+            paths,
+            move |context| {
+                let clean_collections = collections
+                    .into_par_iter()
+                    .filter_map(|collection| {
+                        let result = check::check(
+                            &sprites_path,
+                            std::slice::from_ref(&collection),
+                            Some(&hash_cache),
+                            Some(&context.cancel),
+                            |sprite| context.send(JobMessage::ChangedSprite(sprite.clone())),
+                        );
+                        match result {
+                            Ok(changed_sprites) if changed_sprites.is_empty() => Some(collection),
+                            Ok(_) => None,
+                            Err(e) => {
+                                context.error(e);
+                                None
+                            }
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                if let Err(e) = hash_cache.save() {
+                    context.error(e);
+                }
+                context.cancel.check()?;
+
+                let backup = Backup::new(&sprites_path, "Pack All");
+                let results = pack::pack_collections(
+                    &clean_collections,
+                    &sprites_path,
+                    output_dir.as_deref(),
+                    layout,
+                    Some(&backup),
+                    Some(&context.cancel),
+                    |progress| context.progress(progress),
+                );
+                for result in results {
+                    if let Err(e) = result {
+                        context.error(e);
+                    }
                 }
+                context.cancel.check()?;
+                Ok(JobResult::Packed)
+            },
+        );
+    }
+
+    /// Get the loaded collections that the project does not ignore.
+    /// # Returns
+    /// * `Vec<Collection>` The collections to check and pack
+    fn unignored_collections(&self) -> Vec<Collection> {
+        self.tab
+            .state
+            .loaded_collections
+            .iter()
+            .filter(|collection| !self.tab.project.is_ignored(&collection.name))
+            .cloned()
+            .collect()
+    }
+
+    /// Get the folder that generated atlases are saved to.
+    /// # Returns
+    /// * `Option<PathBuf>` The output directory of the project or else the settings, or `None` to save next to each collection's atlas
+    fn output_dir(&self) -> Option<PathBuf> {
+        let sprites_path = PathBuf::from(self.settings.sprites_path.clone());
+        if let Some(output_dir) = self.tab.project.output_dir(&sprites_path) {
+            Some(output_dir)
+        } else if self.settings.output_dir.is_empty() {
+            None
+        } else {
+            Some(PathBuf::from(self.settings.output_dir.clone()))
+        }
+    }
+
+    /// Get how sprites are laid out in generated atlases.
+    /// # Returns
+    /// * `Layout` The layout chosen in the project, or else in the settings
+    fn layout(&self) -> Layout {
+        if let Some(layout) = self.tab.project.layout {
+            layout
+        } else if self.settings.repack {
+            Layout::Repack {
+                max_size: self.settings.max_atlas_size,
+                padding: self.settings.padding,
+                retrim: self.settings.retrim,
             }
+        } else {
+            Layout::Original
         }
     }
 
-    /// Poll for the progress of the current pack.
-    fn poll_progress(&mut self) {
-        if let Some(rx) = self.progress_receiver.as_mut() {
-            match rx.try_recv() {
-                Ok(progress) => {
-                    if progress < 0. {
-                        self.state.is_packing = false;
-                        return;
+    /// Handle the messages sent by the background jobs of every tab since the last frame.
+    fn poll_jobs(&mut self) {
+        for index in 0..self.tabs.len() {
+            if index != self.current_tab {
+                self.with_tab(index, Self::poll_tab_jobs);
+            }
+        }
+        self.poll_tab_jobs();
+    }
+
+    /// Handle the messages sent by the background jobs of the shown tab since the last frame.
+    fn poll_tab_jobs(&mut self) {
+        for event in self.tab.jobs.poll() {
+            match event.message {
+                JobMessage::Loaded(animation_dump) => {
+                    // An earlier load that was replaced may still be sending what it has loaded
+                    if self.tab.load_job == Some(event.id) {
+                        self.add_loaded_animation(animation_dump);
                     }
-                    self.state.pack_progress = progress;
-                    if progress >= 1. {
-                        self.state.is_packing = false;
+                }
+                JobMessage::Progress(progress) => {
+                    if event.kind == JobKind::Pack || event.kind == JobKind::Preview {
+                        self.tab.state.pack_progress = progress;
+                    }
+                }
+                JobMessage::ChangedSprite(sprite) => {
+                    if !self.tab.state.changed_sprites.contains(&sprite) {
+                        self.tab.state.changed_sprites.push(sprite);
                     }
                 }
-                Err(mpsc::TryRecvError::Empty) => {}
-                Err(mpsc::TryRecvError::Disconnected) => {
-                    self.state.is_packing = false;
+                JobMessage::ExceedingFrame(exceeding_frame) => {
+                    self.tab.state.exceeding_frames.push(exceeding_frame)
+                }
+                JobMessage::EditedFrame(edited_frame) => {
+                    self.tab.state.edited_frames.push(edited_frame)
+                }
+                JobMessage::Error(e) => self.errors.push(e),
+                JobMessage::Finished(result) => self.finish_job(event.id, event.kind, result),
+            }
+        }
+    }
+
+    /// Update the app once a background job has ended.
+    /// # Arguments
+    /// * `id` - The ID of the job
+    /// * `kind` - The kind of job
+    /// * `result` - What the job returned
+    fn finish_job(&mut self, id: usize, kind: JobKind, result: Result<JobResult>) {
+        if let Some(paths) = self.tab.job_writes.remove(&id) {
+            self.tab.own_writes.lock().unwrap().end(&paths);
+        }
+        match kind {
+            JobKind::Load => {
+                if self.tab.load_job == Some(id) {
+                    self.tab.load_job = None;
+                }
+            }
+            JobKind::Check => {
+                self.tab.state.is_checking = false;
+                self.tab.state.can_pack = result.is_ok();
+            }
+            JobKind::Pack => {
+                self.tab.state.is_packing = false;
+                self.tab.state.is_packing_all = false;
+            }
+            JobKind::Preview => self.tab.state.is_packing = false,
+            JobKind::Resolve => {}
+            JobKind::Replace => {
+                if self.tab.state.show_backups {
+                    self.load_backups();
                 }
             }
         }
+
+        match result {
+            Ok(JobResult::Previewed(preview)) => self.tab.preview = Some(preview),
+            Ok(JobResult::Resolved {
+                mut source_sprites,
+                operation_name,
+            }) => match operation_name {
+                Some(operation_name) if !source_sprites.is_empty() => {
+                    self.replace_or_preview(source_sprites, operation_name)
+                }
+                Some(_) => {}
+                // Select the chosen duplicate so that it can be inspected before replacing
+                None => {
+                    if let Some(source_sprite) = source_sprites.pop() {
+                        self.tab.frame_timer = None;
+                        self.tab.state.inspect_mode = InspectMode::Backup;
+                        self.select_changed_sprite(&source_sprite);
+                        self.tab.state.current_frame = source_sprite;
+                    }
+                }
+            },
+            Ok(JobResult::Replaced {
+                operation,
+                source_sprites,
+            }) => {
+                for change in &operation.changes {
+                    self.tab.hash_cache.invalidate(&change.path);
+                }
+                self.tab.state.changed_sprites.retain(|sprite| {
+                    !source_sprites.iter().any(|source_sprite| {
+                        sprite.id == source_sprite.id
+                            && sprite.collection_name == source_sprite.collection_name
+                    })
+                });
+                self.tab.history.push(operation);
+                let sprites_path = PathBuf::from(self.settings.sprites_path.clone());
+                for source_sprite in &source_sprites {
+                    self.tab
+                        .project
+                        .add_resolution(&sprites_path, source_sprite);
+                }
+                self.save_project();
+            }
+            Ok(_) => {}
+            // Cancelling is not a failure, so there is nothing to show for it
+            Err(SpritePackerError::Cancelled) => {}
+            Err(e) => self.errors.push(e),
+        }
+    }
+
+    /// Handle the files that the watchers of every tab saw change on disk.
+    /// # Arguments
+    /// * `ctx` - The egui context
+    fn poll_watcher(&mut self, ctx: &egui::Context) {
+        for index in 0..self.tabs.len() {
+            if index != self.current_tab {
+                self.with_tab(index, |app| app.poll_tab_watcher(ctx));
+            }
+        }
+        self.poll_tab_watcher(ctx);
+    }
+
+    /// Handle the files that the watcher of the shown tab saw change on disk, marking the frames they belong to as modified and refreshing their previews.
+    /// # Arguments
+    /// * `ctx` - The egui context
+    fn poll_tab_watcher(&mut self, ctx: &egui::Context) {
+        let Some(rx) = self.tab.watcher_receiver.as_ref() else {
+            return;
+        };
+        let watch_events = rx.try_iter().collect::<Vec<_>>();
+        let mut refresh_diff = false;
+        for watch_event in watch_events {
+            let (path, is_removed) = match &watch_event {
+                WatchEvent::Changed(path) => (path, false),
+                WatchEvent::Removed(path) => (path, true),
+            };
+            for collection in &self.tab.state.loaded_collections {
+                if self.is_dump_path(&collection.path, path) {
+                    ctx.forget_image(&self.atlas_url(collection));
+                }
+            }
+            for sprite in self.watched_sprites(path) {
+                ctx.forget_image(&self.frame_url(&sprite.path));
+                if sprite.collection_name == self.tab.state.current_collection.name {
+                    self.tab.state.can_pack = false;
+                }
+                refresh_diff |= !is_removed
+                    && self.tab.state.inspect_mode == InspectMode::Diff
+                    && (sprite == self.tab.state.current_frame
+                        || sprite == self.tab.state.diff_sprite);
+                // Worked out here rather than when listing the frame, so that the disk is not read on every repaint
+                let note = if is_removed { "Missing" } else { "Modified" };
+                let label = format!(
+                    "{} ({})",
+                    sprite.name,
+                    translate(note, self.settings.language.clone())
+                );
+                match self
+                    .tab
+                    .state
+                    .modified_sprites
+                    .iter_mut()
+                    .find(|(modified_sprite, _)| *modified_sprite == sprite)
+                {
+                    Some((_, modified_label)) => *modified_label = label,
+                    None => self.tab.state.modified_sprites.push((sprite, label)),
+                }
+            }
+        }
+
+        if refresh_diff {
+            let current_frame = self.tab.state.current_frame.clone();
+            if self.tab.original_texture.is_some() {
+                self.compare_with_atlas(ctx, current_frame);
+            } else {
+                self.compare_sprites(ctx, current_frame, self.tab.state.diff_sprite.clone());
+            }
+        }
+    }
+
+    /// Get the loaded sprites whose frame image is at a path reported by the watcher.
+    /// # Arguments
+    /// * `path` - The path of the file that changed
+    /// # Returns
+    /// * `Vec<Sprite>` The sprites, if the file is a frame
+    fn watched_sprites(&self, path: &Path) -> Vec<Sprite> {
+        self.tab
+            .state
+            .loaded_collections
+            .iter()
+            .flat_map(|collection| &collection.sprites)
+            .filter(|sprite| self.is_dump_path(Path::new(&sprite.path), path))
+            .cloned()
+            .collect()
+    }
+
+    /// Check whether a path stored in the dump refers to a path reported by the watcher.
+    /// # Arguments
+    /// * `dump_path` - The path as stored in the dump, either relative to the sprites path or absolute
+    /// * `path` - The path reported by the watcher
+    /// # Returns
+    /// * `bool` Whether both paths refer to the same file
+    fn is_dump_path(&self, dump_path: &Path, path: &Path) -> bool {
+        dump_path == path || Path::new(&self.settings.sprites_path).join(dump_path) == path
+    }
+
+    /// Store the settings if they changed since they were last stored, so that they are kept even if the app does not exit cleanly.
+    fn store_settings(&mut self) {
+        if self.settings == self.stored_settings {
+            return;
+        }
+        // Remembered even if storing fails, so that the error is only shown once per change
+        self.stored_settings = self.settings.clone();
+        if let Err(e) = confy::store(APP_NAME, APP_NAME, &self.settings) {
+            self.errors.push(e.into());
+        }
+    }
+
+    /// Poll for errors raised by background threads.
+    fn poll_errors(&mut self) {
+        while let Ok(error) = self.error_receiver.try_recv() {
+            self.errors.push(error);
+        }
+    }
+
+    /// Highlight the trim rect of a frame and its opaque pixels outside of it over the frame preview.
+    /// # Arguments
+    /// * `ui` - The UI the preview was added to
+    /// * `preview_rect` - Where the frame preview was drawn
+    /// * `exceeding_frame` - The frame with opaque pixels outside its trim rect
+    fn paint_trim_overlay(ui: &egui::Ui, preview_rect: Rect, exceeding_frame: &ExceedingFrame) {
+        if exceeding_frame.frame_width == 0 || exceeding_frame.frame_height == 0 {
+            return;
+        }
+        let scale = Vec2::new(
+            preview_rect.width() / exceeding_frame.frame_width as f32,
+            preview_rect.height() / exceeding_frame.frame_height as f32,
+        );
+        let to_screen = |sprite: &Sprite| {
+            let top = exceeding_frame.frame_height as i32 - sprite.yr - sprite.height;
+            Rect::from_min_size(
+                preview_rect.min + Vec2::new(sprite.xr as f32, top as f32) * scale,
+                Vec2::new(sprite.width as f32, sprite.height as f32) * scale,
+            )
+        };
+
+        let painter = ui.painter_at(preview_rect);
+        let trim_rect = to_screen(&exceeding_frame.sprite);
+        let opaque_rect = to_screen(&exceeding_frame.trimmed_sprite);
+        painter.rect_filled(
+            opaque_rect,
+            0.,
+            Color32::from_rgba_unmultiplied(255, 0, 0, 48),
+        );
+        painter.rect_stroke(opaque_rect, 0., Stroke::new(1., Color32::RED));
+        painter.rect_stroke(trim_rect, 0., Stroke::new(1., Color32::GREEN));
     }
 
     /// Set the font of the application.
@@ -1157,6 +2738,9 @@ impl App {
     /// # Returns
     /// * `bool` Whether the UI should be enabled
     fn ui_enabled(&self) -> bool {
-        !self.state.is_packing && !self.state.is_checking
+        !self.tab.state.is_packing
+            && !self.tab.state.is_checking
+            && !self.tab.jobs.is_running_except(JobKind::Load)
+            && self.tab.preview.is_none()
     }
 }
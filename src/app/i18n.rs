@@ -1,71 +1,285 @@
 pub fn translate(key: &str, language: String) -> &str {
     match language.as_str() {
         "de" => match key {
+            "All Collections" => "Alle Sammlungen",
             "Animations" => "Animationen",
+            "Apply" => "Anwenden",
+            "Ask Where To Save" => "Speicherort erfragen",
             "Backup" => "Sicherung",
+            "Backups" => "Sicherungen",
+            "Bounds" => "Begrenzung",
             "Browse" => "Durchsuchen",
+            "Cancel" => "Abbrechen",
+            "Cancelled" => "Abgebrochen",
             "Changed" => "Geändert",
+            "Changed Pixels" => "geänderte Pixel",
             "Check" => "Überprüfen",
+            "Clear Finished" => "Beendete entfernen",
             "Clips" => "Clips",
+            "Compare" => "Vergleichen",
+            "Compare With Original" => "Mit Original vergleichen",
             "Dark" => "Dunkel",
+            "Differences" => "Unterschiede",
+            "Different Pixels" => "Abweichende Pixel",
+            "Differs From Atlas" => "Weicht vom Atlas ab",
+            "Dismiss All" => "Alle verwerfen",
+            "Done" => "Fertig",
+            "Edited" => "Bearbeitet",
+            "Edited Frames" => "Bearbeitete Frames",
+            "Errors" => "Fehler",
             "Frames" => "Frames",
+            "History" => "Verlauf",
+            "Ignored Collections" => "Ignorierte Sammlungen",
             "Inspector" => "Inspektor",
+            "Jobs" => "Aufträge",
+            "Kept The Last Time This Sprite Was Resolved" => {
+                "Beim letzten Auflösen dieses Sprites behalten"
+            }
             "Language" => "Sprache",
+            "Layout" => "Anordnung",
+            "Loading" => "Wird geladen",
+            "Majority" => "Mehrheit",
+            "Max Atlas Size" => "Max. Atlasgröße",
+            "Missing" => "Fehlt",
+            "Modified" => "Geändert",
+            "New Layout" => "Neues Layout",
+            "Newest" => "Neueste",
+            "No Changes" => "Keine Änderungen",
+            "Open In New Tab" => "In neuem Tab öffnen",
+            "Original" => "Original",
+            "Output Directory" => "Ausgabeordner",
+            "Outside Trim Bounds" => "Außerhalb des Zuschnitts",
             "Pack" => "Packen",
+            "Pack All" => "Alle packen",
             "Packing" => "Packen",
+            "Padding" => "Abstand",
+            "Preview" => "Vorschau",
+            "Preview Changes" => "Änderungen vorab anzeigen",
+            "Project" => "Projekt",
+            "Re-trim" => "Neu zuschneiden",
+            "Recent" => "Zuletzt geöffnet",
+            "Redo" => "Wiederholen",
+            "Refresh" => "Aktualisieren",
+            "Reload" => "Neu laden",
             "Replace" => "Ersetzen",
+            "Resolutions" => "Auflösungen",
+            "Resolve" => "Auflösen",
+            "Resolve All" => "Alle auflösen",
+            "Restore" => "Wiederherstellen",
+            "Restore All" => "Alle wiederherstellen",
             "Sprites Path" => "Sprites Pfad",
+            "Undo" => "Rückgängig",
             _ => key,
         },
         "es" => match key {
+            "All Collections" => "Todas las colecciones",
             "Animations" => "Animaciones",
+            "Apply" => "Aplicar",
+            "Ask Where To Save" => "Preguntar dónde guardar",
             "Backup" => "Respaldo",
+            "Backups" => "Respaldos",
+            "Bounds" => "Límites",
             "Browse" => "Navegar",
+            "Cancel" => "Cancelar",
+            "Cancelled" => "Cancelado",
             "Changed" => "Cambiado",
+            "Changed Pixels" => "píxeles cambiados",
             "Check" => "Verificar",
+            "Clear Finished" => "Quitar terminados",
             "Clips" => "Clips",
+            "Compare" => "Comparar",
+            "Compare With Original" => "Comparar con el original",
             "Dark" => "Oscuro",
+            "Differences" => "Diferencias",
+            "Different Pixels" => "Píxeles diferentes",
+            "Differs From Atlas" => "Difiere del atlas",
+            "Dismiss All" => "Descartar todo",
+            "Done" => "Hecho",
+            "Edited" => "Editado",
+            "Edited Frames" => "Fotogramas editados",
+            "Errors" => "Errores",
             "Frames" => "Fotogramas",
+            "History" => "Historial",
+            "Ignored Collections" => "Colecciones ignoradas",
             "Inspector" => "Inspector",
+            "Jobs" => "Tareas",
+            "Kept The Last Time This Sprite Was Resolved" => {
+                "Conservado la última vez que se resolvió este sprite"
+            }
             "Language" => "Idioma",
+            "Layout" => "Disposición",
+            "Loading" => "Cargando",
+            "Majority" => "Mayoría",
+            "Max Atlas Size" => "Tamaño máximo del atlas",
+            "Missing" => "Falta",
+            "Modified" => "Modificado",
+            "New Layout" => "Nuevo diseño",
+            "Newest" => "Más reciente",
+            "No Changes" => "Sin cambios",
+            "Open In New Tab" => "Abrir en una pestaña nueva",
+            "Original" => "Original",
+            "Output Directory" => "Directorio de salida",
+            "Outside Trim Bounds" => "Fuera del recorte",
             "Pack" => "Paquete",
+            "Pack All" => "Empaquetar todo",
             "Packing" => "Empaquetando",
+            "Padding" => "Relleno",
+            "Preview" => "Vista previa",
+            "Preview Changes" => "Previsualizar cambios",
+            "Project" => "Proyecto",
+            "Re-trim" => "Recortar de nuevo",
+            "Recent" => "Recientes",
+            "Redo" => "Rehacer",
+            "Refresh" => "Actualizar",
+            "Reload" => "Recargar",
             "Replace" => "Reemplazar",
+            "Resolutions" => "Resoluciones",
+            "Resolve" => "Resolver",
+            "Resolve All" => "Resolver todo",
+            "Restore" => "Restaurar",
+            "Restore All" => "Restaurar todo",
             "Sprites Path" => "Sprites Ruta",
+            "Undo" => "Deshacer",
             _ => key,
         },
         "fr" => match key {
+            "All Collections" => "Toutes les collections",
             "Animations" => "Animations",
+            "Apply" => "Appliquer",
+            "Ask Where To Save" => "Demander où enregistrer",
             "Backup" => "Sauvegarde",
+            "Backups" => "Sauvegardes",
+            "Bounds" => "Limites",
             "Browse" => "Parcourir",
+            "Cancel" => "Annuler",
+            "Cancelled" => "Annulé",
             "Changed" => "Modifié",
+            "Changed Pixels" => "pixels modifiés",
             "Check" => "Vérifier",
+            "Clear Finished" => "Effacer les terminés",
             "Clips" => "Vitesses",
+            "Compare" => "Comparer",
+            "Compare With Original" => "Comparer avec l'original",
             "Dark" => "Sombre",
+            "Differences" => "Différences",
+            "Different Pixels" => "Pixels différents",
+            "Differs From Atlas" => "Diffère de l'atlas",
+            "Dismiss All" => "Tout ignorer",
+            "Done" => "Terminé",
+            "Edited" => "Modifié",
+            "Edited Frames" => "Images modifiées",
+            "Errors" => "Erreurs",
             "Frames" => "Images",
+            "History" => "Historique",
+            "Ignored Collections" => "Collections ignorées",
             "Inspector" => "Inspecteur",
+            "Jobs" => "Tâches",
+            "Kept The Last Time This Sprite Was Resolved" => {
+                "Conservé lors de la dernière résolution de ce sprite"
+            }
             "Language" => "Langue",
+            "Layout" => "Disposition",
+            "Loading" => "Chargement",
+            "Majority" => "Majorité",
+            "Max Atlas Size" => "Taille max. de l'atlas",
+            "Missing" => "Manquant",
+            "Modified" => "Modifié",
+            "New Layout" => "Nouvelle disposition",
+            "Newest" => "Plus récent",
+            "No Changes" => "Aucun changement",
+            "Open In New Tab" => "Ouvrir dans un nouvel onglet",
+            "Original" => "Original",
+            "Output Directory" => "Dossier de sortie",
+            "Outside Trim Bounds" => "Hors du recadrage",
             "Pack" => "Pack",
+            "Pack All" => "Tout packer",
             "Packing" => "Packaging",
+            "Padding" => "Marge",
+            "Preview" => "Aperçu",
+            "Preview Changes" => "Prévisualiser les changements",
+            "Project" => "Projet",
+            "Re-trim" => "Recadrer",
+            "Recent" => "Récents",
+            "Redo" => "Rétablir",
+            "Refresh" => "Actualiser",
+            "Reload" => "Recharger",
             "Replace" => "Remplacer",
+            "Resolutions" => "Résolutions",
+            "Resolve" => "Résoudre",
+            "Resolve All" => "Tout résoudre",
+            "Restore" => "Restaurer",
+            "Restore All" => "Tout restaurer",
             "Sprites Path" => "Sprites Chemin",
+            "Undo" => "Annuler",
             _ => key,
         },
         "zh-CN" => match key {
+            "All Collections" => "所有集合",
             "Animations" => "动画",
+            "Apply" => "应用",
+            "Ask Where To Save" => "询问保存位置",
             "Backup" => "备份",
+            "Backups" => "备份列表",
+            "Bounds" => "边界",
             "Browse" => "浏览",
+            "Cancel" => "取消",
+            "Cancelled" => "已取消",
             "Changed" => "更改",
+            "Changed Pixels" => "个像素已更改",
             "Check" => "检查",
+            "Clear Finished" => "清除已完成",
             "Clips" => "剪辑",
+            "Compare" => "比较",
+            "Compare With Original" => "与原图比较",
             "Dark" => "黑暗",
+            "Differences" => "差异",
+            "Different Pixels" => "不同像素",
+            "Differs From Atlas" => "与图集不同",
+            "Dismiss All" => "全部忽略",
+            "Done" => "完成",
+            "Edited" => "已编辑",
+            "Edited Frames" => "已编辑的帧",
+            "Errors" => "错误",
             "Frames" => "帧",
+            "History" => "历史",
+            "Ignored Collections" => "忽略的集合",
             "Inspector" => "检查员",
+            "Jobs" => "任务",
+            "Kept The Last Time This Sprite Was Resolved" => "上次解决此精灵时保留的副本",
             "Language" => "语言",
+            "Layout" => "布局",
+            "Loading" => "加载中",
+            "Majority" => "多数",
+            "Max Atlas Size" => "最大图集尺寸",
+            "Missing" => "缺失",
+            "Modified" => "已修改",
+            "New Layout" => "新布局",
+            "Newest" => "最新",
+            "No Changes" => "没有更改",
+            "Open In New Tab" => "在新标签页中打开",
+            "Original" => "原图",
+            "Output Directory" => "输出目录",
+            "Outside Trim Bounds" => "超出裁剪范围",
             "Pack" => "打包",
+            "Pack All" => "全部打包",
             "Packing" => "打包中",
+            "Padding" => "间距",
+            "Preview" => "预览",
+            "Preview Changes" => "预览更改",
+            "Project" => "项目",
+            "Re-trim" => "重新裁剪",
+            "Recent" => "最近打开",
+            "Redo" => "重做",
+            "Refresh" => "刷新",
+            "Reload" => "重新加载",
             "Replace" => "替换",
+            "Resolutions" => "已解决的选择",
+            "Resolve" => "解决",
+            "Resolve All" => "全部解决",
+            "Restore" => "恢复",
+            "Restore All" => "全部恢复",
             "Sprites Path" => "精灵路径",
+            "Undo" => "撤销",
             _ => key,
         },
         _ => key,
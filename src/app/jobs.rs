@@ -0,0 +1,310 @@
+use std::{
+    sync::mpsc::{self, Receiver, Sender, TryRecvError},
+    thread,
+    time::{Duration, Instant},
+};
+
+use spritepacker::error::{Result, SpritePackerError};
+use spritepacker::packer::{
+    cancel::CancelToken, history::Operation, load::Dump, trim::ExceedingFrame,
+};
+use spritepacker::tk2d::sprite::Sprite;
+
+use super::app::Preview;
+
+/// The kinds of operations that run as background jobs.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum JobKind {
+    /// Loading the collections and animations of a dump
+    Load,
+    /// Checking collections for changed, clipped and edited sprites
+    Check,
+    /// Packing one or more collections
+    Pack,
+    /// Working out what packing or replacing would write, without writing it
+    Preview,
+    /// Choosing which duplicate of each changed sprite to keep
+    Resolve,
+    /// Replacing duplicates
+    Replace,
+}
+
+/// Where a job is in its lifetime.
+#[derive(Clone, Debug, PartialEq)]
+pub enum JobStatus {
+    Running,
+    Succeeded,
+    Cancelled,
+    /// The job stopped early because of the given error
+    Failed(String),
+}
+
+/// What a job hands back to the app once it has succeeded.
+pub enum JobResult {
+    /// Every animation folder was loaded
+    Loaded,
+    /// Every collection was checked
+    Checked,
+    /// Atlases were packed and saved
+    Packed,
+    /// The files an operation would write, for the user to confirm
+    Previewed(Preview),
+    /// A duplicate was chosen for each changed sprite that the strategy could decide on
+    Resolved {
+        /// The duplicates to replace the others with
+        source_sprites: Vec<Sprite>,
+        /// The name of the operation to replace the duplicates in, or `None` to only select the chosen duplicate
+        operation_name: Option<String>,
+    },
+    /// Duplicates were replaced
+    Replaced {
+        /// The files that were overwritten, for undoing and redoing
+        operation: Operation,
+        /// The sprites whose duplicates were all replaced
+        source_sprites: Vec<Sprite>,
+    },
+}
+
+/// A message sent from a job to the app while it runs.
+pub enum JobMessage {
+    /// The fraction of the job that is done
+    Progress(f32),
+    /// An animation folder that was loaded, with the sprites it adds to each collection
+    Loaded(Dump),
+    /// A sprite that differs from one of its duplicates
+    ChangedSprite(Sprite),
+    /// A frame with opaque pixels outside its trim rect
+    ExceedingFrame(ExceedingFrame),
+    /// A frame that differs from the atlas it was dumped from
+    EditedFrame(Sprite),
+    /// A problem that did not stop the job
+    Error(SpritePackerError),
+    /// The job has ended, either with its result or with the error that stopped it
+    Finished(Result<JobResult>),
+}
+
+/// What a job's worker thread uses to talk to the app.
+pub struct JobContext {
+    sender: Sender<JobMessage>,
+    /// The token that is cancelled when the user asks the job to stop
+    pub cancel: CancelToken,
+}
+
+impl JobContext {
+    /// Send a message to the app, ignoring it if the app has stopped listening.
+    /// # Arguments
+    /// * `message` - The message
+    pub fn send(&self, message: JobMessage) {
+        let _ = self.sender.send(message);
+    }
+
+    /// Report the fraction of the job that is done.
+    /// # Arguments
+    /// * `progress` - The fraction between 0 and 1
+    pub fn progress(&self, progress: f32) {
+        self.send(JobMessage::Progress(progress));
+    }
+
+    /// Report a problem that does not stop the job.
+    /// Cancellations are left out, as the job's status already shows them.
+    /// # Arguments
+    /// * `error` - The problem
+    pub fn error(&self, error: SpritePackerError) {
+        if !matches!(error, SpritePackerError::Cancelled) {
+            self.send(JobMessage::Error(error));
+        }
+    }
+}
+
+/// An operation running, or that ran, on a background thread.
+pub struct Job {
+    pub id: usize,
+    pub kind: JobKind,
+    /// A short description of the job, e.g. `Pack Knight`
+    pub name: String,
+    pub status: JobStatus,
+    /// The fraction of the job that is done, if it reports its progress
+    pub progress: Option<f32>,
+    /// The number of problems reported that did not stop the job
+    pub errors: usize,
+    started: Instant,
+    finished: Option<Instant>,
+    cancel: CancelToken,
+    receiver: Receiver<JobMessage>,
+}
+
+impl Job {
+    /// Get how long the job has been running, or how long it ran for.
+    /// # Returns
+    /// * `Duration` The time since the job started, up to when it finished
+    pub fn elapsed(&self) -> Duration {
+        self.finished.unwrap_or_else(Instant::now) - self.started
+    }
+
+    /// Check whether the job is still running.
+    /// # Returns
+    /// * `bool` Whether the job has not finished yet
+    pub fn is_running(&self) -> bool {
+        self.status == JobStatus::Running
+    }
+}
+
+/// A message received from a job, along with which job sent it.
+pub struct JobEvent {
+    pub id: usize,
+    pub kind: JobKind,
+    pub message: JobMessage,
+}
+
+/// Every job started since the app was opened, or since finished jobs were last cleared.
+#[derive(Default)]
+pub struct Jobs {
+    pub jobs: Vec<Job>,
+    next_id: usize,
+}
+
+impl Jobs {
+    /// Run a job on a new thread.
+    /// # Arguments
+    /// * `kind` - The kind of job
+    /// * `name` - A short description of the job
+    /// * `work` - The job itself, which reports progress through its context and returns its result
+    /// # Returns
+    /// * `usize` The ID of the new job
+    pub fn spawn(
+        &mut self,
+        kind: JobKind,
+        name: String,
+        work: impl FnOnce(&JobContext) -> Result<JobResult> + Send + 'static,
+    ) -> usize {
+        let (sender, receiver) = mpsc::channel();
+        let cancel = CancelToken::new();
+        let context = JobContext {
+            sender,
+            cancel: cancel.clone(),
+        };
+        thread::spawn(move || {
+            let result = work(&context);
+            context.send(JobMessage::Finished(result));
+        });
+
+        let id = self.next_id;
+        self.next_id += 1;
+        self.jobs.push(Job {
+            id,
+            kind,
+            name,
+            status: JobStatus::Running,
+            progress: None,
+            errors: 0,
+            started: Instant::now(),
+            finished: None,
+            cancel,
+            receiver,
+        });
+        id
+    }
+
+    /// Receive every message sent by running jobs since the last poll, updating their status and progress.
+    /// # Returns
+    /// * `Vec<JobEvent>` The messages, in the order each job sent them
+    pub fn poll(&mut self) -> Vec<JobEvent> {
+        let mut events = vec![];
+        for job in self.jobs.iter_mut().filter(|job| job.is_running()) {
+            loop {
+                let message = match job.receiver.try_recv() {
+                    Ok(message) => message,
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => {
+                        // The worker thread panicked before it could report how it ended
+                        JobMessage::Finished(Err(SpritePackerError::JobPanicked(job.name.clone())))
+                    }
+                };
+                match &message {
+                    JobMessage::Progress(progress) => job.progress = Some(*progress),
+                    JobMessage::Error(_) => job.errors += 1,
+                    JobMessage::Finished(result) => {
+                        job.status = match result {
+                            Ok(_) => JobStatus::Succeeded,
+                            Err(SpritePackerError::Cancelled) => JobStatus::Cancelled,
+                            Err(e) => JobStatus::Failed(e.to_string()),
+                        };
+                        job.finished = Some(Instant::now());
+                    }
+                    _ => {}
+                }
+                let is_finished = matches!(message, JobMessage::Finished(_));
+                events.push(JobEvent {
+                    id: job.id,
+                    kind: job.kind,
+                    message,
+                });
+                if is_finished {
+                    break;
+                }
+            }
+        }
+        events
+    }
+
+    /// Check whether any job is still running.
+    /// # Returns
+    /// * `bool` Whether a job has not finished yet
+    pub fn is_running(&self) -> bool {
+        self.jobs.iter().any(Job::is_running)
+    }
+
+    /// Check whether any job of a kind other than the given one is still running.
+    /// # Arguments
+    /// * `kind` - The kind of job to leave out
+    /// # Returns
+    /// * `bool` Whether a job of another kind has not finished yet
+    pub fn is_running_except(&self, kind: JobKind) -> bool {
+        self.jobs
+            .iter()
+            .any(|job| job.kind != kind && job.is_running())
+    }
+
+    /// Get a job by its ID.
+    /// # Arguments
+    /// * `id` - The ID of the job
+    /// # Returns
+    /// * `Option<&Job>` The job, unless it has been cleared
+    pub fn get(&self, id: usize) -> Option<&Job> {
+        self.jobs.iter().find(|job| job.id == id)
+    }
+
+    /// Ask a job to stop. It is marked as cancelled once it has stopped.
+    /// # Arguments
+    /// * `id` - The ID of the job
+    pub fn cancel(&self, id: usize) {
+        if let Some(job) = self.get(id) {
+            job.cancel.cancel();
+        }
+    }
+
+    /// Ask every running job of a kind to stop.
+    /// # Arguments
+    /// * `kind` - The kind of job
+    pub fn cancel_kind(&self, kind: JobKind) {
+        for job in self
+            .jobs
+            .iter()
+            .filter(|job| job.kind == kind && job.is_running())
+        {
+            job.cancel.cancel();
+        }
+    }
+
+    /// Ask every running job to stop.
+    pub fn cancel_all(&self) {
+        for job in self.jobs.iter().filter(|job| job.is_running()) {
+            job.cancel.cancel();
+        }
+    }
+
+    /// Forget every job that has finished.
+    pub fn clear_finished(&mut self) {
+        self.jobs.retain(Job::is_running);
+    }
+}
@@ -1,3 +1,5 @@
 pub mod app;
 pub mod i18n;
+pub mod jobs;
 pub mod settings;
+pub mod writes;
@@ -1,4 +1,8 @@
 use serde::{Deserialize, Serialize};
+use spritepacker::packer::resolve::Strategy;
+
+/// The number of recently opened sprites paths that are remembered.
+const MAX_RECENT_PATHS: usize = 10;
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Settings {
@@ -8,6 +12,24 @@ pub struct Settings {
     pub sprites_path: String,
     #[serde(rename = "Dark")]
     pub dark: bool,
+    #[serde(default, rename = "Output Directory")]
+    pub output_dir: String,
+    #[serde(default, rename = "Ask Where To Save")]
+    pub ask_save_path: bool,
+    #[serde(default, rename = "New Layout")]
+    pub repack: bool,
+    #[serde(default = "default_max_atlas_size", rename = "Max Atlas Size")]
+    pub max_atlas_size: u32,
+    #[serde(default = "default_padding", rename = "Padding")]
+    pub padding: u32,
+    #[serde(default, rename = "Re-trim")]
+    pub retrim: bool,
+    #[serde(default, rename = "Preview Changes")]
+    pub preview_changes: bool,
+    #[serde(default, rename = "Resolve Strategy")]
+    pub resolve_strategy: Strategy,
+    #[serde(default, rename = "Recent Paths")]
+    pub recent_paths: Vec<String>,
 }
 
 impl Default for Settings {
@@ -16,6 +38,34 @@ impl Default for Settings {
             language: "en-US".to_string(),
             sprites_path: "".to_string(),
             dark: true,
+            output_dir: "".to_string(),
+            ask_save_path: false,
+            repack: false,
+            max_atlas_size: default_max_atlas_size(),
+            padding: default_padding(),
+            retrim: false,
+            preview_changes: false,
+            resolve_strategy: Strategy::default(),
+            recent_paths: vec![],
         }
     }
 }
+
+impl Settings {
+    /// Move a sprites path to the top of the recently opened paths, forgetting the oldest ones past the limit.
+    /// # Arguments
+    /// * `sprites_path` - The path that was opened
+    pub fn add_recent_path(&mut self, sprites_path: &str) {
+        self.recent_paths.retain(|path| path != sprites_path);
+        self.recent_paths.insert(0, sprites_path.to_string());
+        self.recent_paths.truncate(MAX_RECENT_PATHS);
+    }
+}
+
+fn default_max_atlas_size() -> u32 {
+    4096
+}
+
+fn default_padding() -> u32 {
+    1
+}
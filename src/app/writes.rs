@@ -0,0 +1,70 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+/// When a file was last modified and how large it is, or `None` if it does not exist.
+type FileStamp = Option<(SystemTime, u64)>;
+
+/// The files in a sprites folder that the app itself is writing or has written,
+/// so that the watcher can tell the app's own writes apart from changes made outside of it.
+#[derive(Debug, Default)]
+pub struct OwnWrites {
+    /// The files being written, with how many writes to each are in progress
+    writing: HashMap<PathBuf, usize>,
+    /// How each file was left when the app last finished writing to it
+    written: HashMap<PathBuf, FileStamp>,
+}
+
+impl OwnWrites {
+    /// Note that the app is about to write to files.
+    /// # Arguments
+    /// * `paths` - The files that may be written
+    pub fn begin(&mut self, paths: &[PathBuf]) {
+        for path in paths {
+            *self.writing.entry(path.clone()).or_default() += 1;
+        }
+    }
+
+    /// Note that the app has finished writing to files, remembering how it left them.
+    /// # Arguments
+    /// * `paths` - The files given to `begin`
+    pub fn end(&mut self, paths: &[PathBuf]) {
+        for path in paths {
+            if let Some(count) = self.writing.get_mut(path) {
+                *count -= 1;
+                if *count == 0 {
+                    self.writing.remove(path);
+                }
+            }
+            self.written.insert(path.clone(), file_stamp(path));
+        }
+    }
+
+    /// Check whether the latest change to a file was made by the app.
+    /// A file counts as the app's own while it is being written and for as long as it is left as the app wrote it,
+    /// so a change made outside of the app is still noticed, even to a file the app wrote before.
+    /// # Arguments
+    /// * `path` - The file that changed
+    /// # Returns
+    /// * `bool` Whether the change was made by the app
+    pub fn is_own(&self, path: &Path) -> bool {
+        self.writing.contains_key(path)
+            || self
+                .written
+                .get(path)
+                .is_some_and(|stamp| *stamp == file_stamp(path))
+    }
+}
+
+/// Get when a file was last modified and how large it is.
+/// # Arguments
+/// * `path` - The path to the file
+/// # Returns
+/// * `FileStamp` The modification time and size, or `None` if the file does not exist
+fn file_stamp(path: &Path) -> FileStamp {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
@@ -0,0 +1,699 @@
+use std::{
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+
+use spritepacker::packer::{
+    backup::{self, Backup},
+    cache::HashCache,
+    check,
+    load::{self, Dump},
+    pack::{self, Layout, PackReport},
+    project::Project,
+    replace,
+    resolve::{self, Strategy},
+    trim,
+};
+use spritepacker::tk2d::cln::Collection;
+use spritepacker::{Result, SpritePackerError};
+
+/// Exit code returned when changed sprites are found.
+const EXIT_CHANGED: u8 = 1;
+/// Exit code returned when an operation fails.
+const EXIT_FAILURE: u8 = 2;
+
+#[derive(Parser)]
+#[command(version, about)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Check whether every sprite in a collection is identical to its duplicates
+    Check {
+        /// The path to the sprites folder dumped by GODump
+        sprites_path: PathBuf,
+        /// The name of the collection to check
+        collection: String,
+    },
+    /// Replace all duplicates of a sprite with that sprite
+    Replace {
+        /// The path to the sprites folder dumped by GODump
+        sprites_path: PathBuf,
+        /// The name of the collection the sprite belongs to
+        collection: String,
+        /// The file name of the sprite to replace duplicates with
+        sprite: String,
+        /// List the frames that would be overwritten without writing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Replace the duplicates of every changed sprite, choosing which duplicate to keep automatically
+    Resolve {
+        /// The path to the sprites folder dumped by GODump
+        sprites_path: PathBuf,
+        /// The name of the collection to resolve; defaults to every collection
+        collection: Option<String>,
+        /// How to choose which duplicate to keep
+        #[arg(long, value_enum, default_value_t = StrategyArg::Newest)]
+        strategy: StrategyArg,
+        /// List the frames that would be overwritten without writing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// List the frames of a collection that differ from the atlas they were dumped from
+    Edited {
+        /// The path to the sprites folder dumped by GODump
+        sprites_path: PathBuf,
+        /// The name of the collection to check
+        collection: String,
+    },
+    /// List the frames of a collection with opaque pixels outside their trim rect
+    Trim {
+        /// The path to the sprites folder dumped by GODump
+        sprites_path: PathBuf,
+        /// The name of the collection to check
+        collection: String,
+    },
+    /// Check a collection and pack it into an atlas
+    Pack {
+        /// The path to the sprites folder dumped by GODump
+        sprites_path: PathBuf,
+        /// The name of the collection to pack
+        collection: String,
+        /// Where to save the generated atlas; defaults to `0.Atlases/Packed/<COLLECTION>.png` next to the collection's atlas
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        #[command(flatten)]
+        layout: LayoutArgs,
+        /// List the files and atlas regions that would be written without writing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Check every collection and pack each one whose duplicates are identical
+    PackAll {
+        /// The path to the sprites folder dumped by GODump
+        sprites_path: PathBuf,
+        /// The folder to save the generated atlases to; defaults to a `Packed` folder next to each collection's atlas
+        #[arg(short, long)]
+        output_dir: Option<PathBuf>,
+        #[command(flatten)]
+        layout: LayoutArgs,
+        /// List the files and atlas regions that would be written without writing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// List the backups of files overwritten by replacing or packing, newest first
+    Backups {
+        /// The path to the sprites folder dumped by GODump
+        sprites_path: PathBuf,
+    },
+    /// Restore the files in a backup to where they were backed up from
+    Restore {
+        /// The path to the sprites folder dumped by GODump
+        sprites_path: PathBuf,
+        /// The name of the backup folder, as listed by `backups`
+        backup: String,
+        /// The original paths of the files to restore; defaults to every file in the backup
+        files: Vec<PathBuf>,
+    },
+}
+
+#[derive(Args)]
+pub struct LayoutArgs {
+    /// Lay the sprites out into a new atlas instead of painting over the original one
+    #[arg(long)]
+    repack: bool,
+    /// The largest width and height a new atlas may have
+    #[arg(long, default_value_t = 4096)]
+    max_size: u32,
+    /// The number of empty pixels to leave between sprites in a new atlas
+    #[arg(long, default_value_t = 1)]
+    padding: u32,
+    /// Fit the trim rect of each sprite to the opaque pixels of its frames before laying it out
+    #[arg(long, requires = "repack")]
+    retrim: bool,
+}
+
+impl From<LayoutArgs> for Layout {
+    fn from(args: LayoutArgs) -> Self {
+        if args.repack {
+            Layout::Repack {
+                max_size: args.max_size,
+                padding: args.padding,
+                retrim: args.retrim,
+            }
+        } else {
+            Layout::Original
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum StrategyArg {
+    /// Keep the duplicate whose frame was modified most recently
+    Newest,
+    /// Keep the duplicate that differs from the collection's atlas
+    Atlas,
+    /// Keep the duplicate that is identical to the most others
+    Majority,
+}
+
+impl From<StrategyArg> for Strategy {
+    fn from(arg: StrategyArg) -> Self {
+        match arg {
+            StrategyArg::Newest => Strategy::Newest,
+            StrategyArg::Atlas => Strategy::DiffersFromAtlas,
+            StrategyArg::Majority => Strategy::Majority,
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    run(Cli::parse().command)
+}
+
+/// Run a command without the GUI.
+/// # Arguments
+/// * `command` - The command to run
+/// # Returns
+/// * `ExitCode` Success, `EXIT_CHANGED` if changed sprites were found, or `EXIT_FAILURE`
+pub fn run(command: Command) -> ExitCode {
+    match run_command(command) {
+        Ok(exit_code) => exit_code,
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::from(EXIT_FAILURE)
+        }
+    }
+}
+
+/// Run a command, propagating any error.
+/// # Arguments
+/// * `command` - The command to run
+/// # Returns
+/// * `Result<ExitCode>` Success or `EXIT_CHANGED` if changed sprites were found
+fn run_command(command: Command) -> Result<ExitCode> {
+    match command {
+        Command::Check {
+            sprites_path,
+            collection,
+        } => {
+            let collection = find_collection(&sprites_path, &collection)?;
+            let cache = HashCache::open(&sprites_path);
+            let is_clean = run_check(&sprites_path, &collection, &cache)?;
+            save_cache(&cache);
+            if is_clean {
+                Ok(ExitCode::SUCCESS)
+            } else {
+                Ok(ExitCode::from(EXIT_CHANGED))
+            }
+        }
+        Command::Replace {
+            sprites_path,
+            collection,
+            sprite,
+            dry_run,
+        } => {
+            let collection = find_collection(&sprites_path, &collection)?;
+            let Some(source_sprite) = collection
+                .sprites
+                .iter()
+                .find(|s| s.name == sprite || s.path == sprite)
+            else {
+                eprintln!(
+                    "Sprite {sprite} not found in collection {}",
+                    collection.name
+                );
+                return Ok(ExitCode::from(EXIT_FAILURE));
+            };
+            if dry_run {
+                let reports = replace::dry_run_replace(&sprites_path, &collection, source_sprite)?;
+                for report in &reports {
+                    println!(
+                        "Would overwrite {} ({} pixels changed)",
+                        report.path.display(),
+                        report.changed_pixels
+                    );
+                }
+                if reports.is_empty() {
+                    println!("Duplicates of {} are already identical", source_sprite.name);
+                }
+                return Ok(ExitCode::SUCCESS);
+            }
+            let mut project = Project::open(&sprites_path)?;
+            let backup = Backup::new(&sprites_path, &format!("Replace {}", source_sprite.name));
+            replace::replace_duplicate_sprites(
+                &sprites_path,
+                &collection,
+                source_sprite,
+                Some(&backup),
+                &mut vec![],
+            )?;
+            println!("Replaced duplicates of {}", source_sprite.name);
+            project.add_resolution(&sprites_path, source_sprite);
+            save_project(&project, &sprites_path);
+            Ok(ExitCode::SUCCESS)
+        }
+        Command::Resolve {
+            sprites_path,
+            collection,
+            strategy,
+            dry_run,
+        } => {
+            let mut project = Project::open(&sprites_path)?;
+            let mut collections = load_dump(&sprites_path)?.collections;
+            if let Some(collection_name) = collection {
+                collections.retain(|collection| collection.name == collection_name);
+                if collections.is_empty() {
+                    return Err(SpritePackerError::CollectionNotFound(collection_name));
+                }
+            } else {
+                collections.retain(|collection| !project.is_ignored(&collection.name));
+            }
+            let cache = HashCache::open(&sprites_path);
+            let changed_sprites =
+                check::check(&sprites_path, &collections, Some(&cache), None, |_| {})?;
+            save_cache(&cache);
+            let conflicts = resolve::choose_sources(
+                &sprites_path,
+                &collections,
+                &changed_sprites,
+                strategy.into(),
+                Some(&cache),
+                Some(&project),
+                None,
+            )?;
+            save_cache(&cache);
+
+            let backup = Backup::new(&sprites_path, "Resolve All");
+            let mut has_unresolved = false;
+            for conflict in &conflicts {
+                let Some(source_sprite) = &conflict.source else {
+                    let names = conflict
+                        .duplicates
+                        .iter()
+                        .map(|sprite| sprite.name.clone())
+                        .collect::<Vec<_>>();
+                    eprintln!(
+                        "{}",
+                        SpritePackerError::AmbiguousDuplicates(names.join(", "))
+                    );
+                    has_unresolved = true;
+                    continue;
+                };
+                let collection = collections
+                    .iter()
+                    .find(|collection| collection.name == source_sprite.collection_name)
+                    .ok_or_else(|| {
+                        SpritePackerError::CollectionNotFound(source_sprite.collection_name.clone())
+                    })?;
+                if dry_run {
+                    for report in
+                        replace::dry_run_replace(&sprites_path, collection, source_sprite)?
+                    {
+                        println!(
+                            "Would overwrite {} with {} ({} pixels changed)",
+                            report.path.display(),
+                            source_sprite.name,
+                            report.changed_pixels
+                        );
+                    }
+                } else {
+                    replace::replace_duplicate_sprites(
+                        &sprites_path,
+                        collection,
+                        source_sprite,
+                        Some(&backup),
+                        &mut vec![],
+                    )?;
+                    println!("Replaced duplicates of {}", source_sprite.name);
+                    project.add_resolution(&sprites_path, source_sprite);
+                }
+            }
+            if !dry_run {
+                save_project(&project, &sprites_path);
+            }
+
+            if has_unresolved {
+                Ok(ExitCode::from(EXIT_CHANGED))
+            } else {
+                Ok(ExitCode::SUCCESS)
+            }
+        }
+        Command::Edited {
+            sprites_path,
+            collection,
+        } => {
+            let collection = find_collection(&sprites_path, &collection)?;
+            let cache = HashCache::open(&sprites_path);
+            let edited_frames =
+                check::find_edited_frames(&sprites_path, &collection, Some(&cache))?;
+            save_cache(&cache);
+            for sprite in &edited_frames {
+                println!("Edited: {}", sprite.path);
+            }
+            if edited_frames.is_empty() {
+                Ok(ExitCode::SUCCESS)
+            } else {
+                Ok(ExitCode::from(EXIT_CHANGED))
+            }
+        }
+        Command::Trim {
+            sprites_path,
+            collection,
+        } => {
+            let collection = find_collection(&sprites_path, &collection)?;
+            let cache = HashCache::open(&sprites_path);
+            let exceeding_frames =
+                trim::find_exceeding_frames(&sprites_path, &collection, Some(&cache))?;
+            save_cache(&cache);
+            for frame in &exceeding_frames {
+                let sprite = &frame.sprite;
+                let trimmed_sprite = &frame.trimmed_sprite;
+                println!(
+                    "Exceeds trim rect: {} ({}, {}, {}x{} -> {}, {}, {}x{})",
+                    sprite.path,
+                    sprite.xr,
+                    sprite.yr,
+                    sprite.width,
+                    sprite.height,
+                    trimmed_sprite.xr,
+                    trimmed_sprite.yr,
+                    trimmed_sprite.width,
+                    trimmed_sprite.height
+                );
+            }
+            if exceeding_frames.is_empty() {
+                Ok(ExitCode::SUCCESS)
+            } else {
+                Ok(ExitCode::from(EXIT_CHANGED))
+            }
+        }
+        Command::Pack {
+            sprites_path,
+            collection,
+            output,
+            layout,
+            dry_run,
+        } => {
+            let collection = find_collection(&sprites_path, &collection)?;
+            let cache = HashCache::open(&sprites_path);
+            let is_clean = run_check(&sprites_path, &collection, &cache)?;
+            save_cache(&cache);
+            if !is_clean {
+                eprintln!(
+                    "Not packing {} until its duplicates are identical",
+                    collection.name
+                );
+                return Ok(ExitCode::from(EXIT_CHANGED));
+            }
+            let project = Project::open(&sprites_path)?;
+            let output = output.unwrap_or_else(|| {
+                pack::atlas_output_path(&collection, project.output_dir(&sprites_path).as_deref())
+            });
+            let layout = project_layout(layout, &project);
+            if dry_run {
+                let report =
+                    pack::dry_run_pack(&collection, &sprites_path, &output, layout, None, |_| {})?;
+                print_pack_report(&report);
+                return Ok(ExitCode::SUCCESS);
+            }
+            let backup = Backup::new(&sprites_path, &format!("Pack {}", collection.name));
+            pack::pack_and_save(
+                &collection,
+                &sprites_path,
+                &output,
+                layout,
+                Some(&backup),
+                None,
+                |_| {},
+            )?;
+            println!("Packed {} into {}", collection.name, output.display());
+            Ok(ExitCode::SUCCESS)
+        }
+        Command::PackAll {
+            sprites_path,
+            output_dir,
+            layout,
+            dry_run,
+        } => {
+            let project = Project::open(&sprites_path)?;
+            let output_dir = output_dir.or_else(|| project.output_dir(&sprites_path));
+            let dump = load_dump(&sprites_path)?;
+            let mut has_changed = false;
+            let mut has_failed = false;
+            let mut clean_collections = vec![];
+            let cache = HashCache::open(&sprites_path);
+            for collection in dump.collections {
+                if project.is_ignored(&collection.name) {
+                    println!("Skipped {} as the project ignores it", collection.name);
+                    continue;
+                }
+                match run_check(&sprites_path, &collection, &cache) {
+                    Ok(true) => clean_collections.push(collection),
+                    Ok(false) => {
+                        eprintln!(
+                            "Not packing {} until its duplicates are identical",
+                            collection.name
+                        );
+                        has_changed = true;
+                    }
+                    Err(e) => {
+                        eprintln!("{e}");
+                        has_failed = true;
+                    }
+                }
+            }
+            save_cache(&cache);
+
+            let layout = project_layout(layout, &project);
+            if dry_run {
+                for collection in &clean_collections {
+                    let atlas_path = pack::atlas_output_path(collection, output_dir.as_deref());
+                    match pack::dry_run_pack(
+                        collection,
+                        &sprites_path,
+                        &atlas_path,
+                        layout,
+                        None,
+                        |_| {},
+                    ) {
+                        Ok(report) => print_pack_report(&report),
+                        Err(e) => {
+                            eprintln!("{e}");
+                            has_failed = true;
+                        }
+                    }
+                }
+            } else {
+                let backup = Backup::new(&sprites_path, "Pack All");
+                let results = pack::pack_collections(
+                    &clean_collections,
+                    &sprites_path,
+                    output_dir.as_deref(),
+                    layout,
+                    Some(&backup),
+                    None,
+                    |_| {},
+                );
+                for (collection, result) in clean_collections.iter().zip(results) {
+                    match result {
+                        Ok(atlas_path) => {
+                            println!("Packed {} into {}", collection.name, atlas_path.display())
+                        }
+                        Err(e) => {
+                            eprintln!("{e}");
+                            has_failed = true;
+                        }
+                    }
+                }
+            }
+
+            if has_failed {
+                Ok(ExitCode::from(EXIT_FAILURE))
+            } else if has_changed {
+                Ok(ExitCode::from(EXIT_CHANGED))
+            } else {
+                Ok(ExitCode::SUCCESS)
+            }
+        }
+        Command::Backups { sprites_path } => {
+            for backup in backup::list_backups(&sprites_path)? {
+                let folder_name = backup
+                    .path
+                    .file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy();
+                println!(
+                    "{folder_name}: {} at {}",
+                    backup.operation,
+                    backup::format_timestamp(backup.timestamp)
+                );
+                for file in backup.files() {
+                    println!("    {}", file.original_path.display());
+                }
+            }
+            Ok(ExitCode::SUCCESS)
+        }
+        Command::Restore {
+            sprites_path,
+            backup,
+            files,
+        } => {
+            let backup_path = sprites_path.join(backup::BACKUPS_DIR_NAME).join(backup);
+            let backup = Backup::open(&backup_path)?;
+            let backup_files = backup.files();
+            let mut has_missing = false;
+            for file in &files {
+                if !backup_files
+                    .iter()
+                    .any(|backup_file| is_same_file(file, &backup_file.original_path))
+                {
+                    eprintln!(
+                        "{} is not in backup {}",
+                        file.display(),
+                        backup_path.display()
+                    );
+                    has_missing = true;
+                }
+            }
+            for backup_file in &backup_files {
+                if files.is_empty()
+                    || files
+                        .iter()
+                        .any(|file| is_same_file(file, &backup_file.original_path))
+                {
+                    backup.restore_file(backup_file)?;
+                    println!("Restored {}", backup_file.original_path.display());
+                }
+            }
+            if has_missing {
+                Ok(ExitCode::from(EXIT_FAILURE))
+            } else {
+                Ok(ExitCode::SUCCESS)
+            }
+        }
+    }
+}
+
+/// Load a dump, printing any problems that were skipped.
+/// # Arguments
+/// * `sprites_path` - The path to the sprites folder
+/// # Returns
+/// * `Result<Dump>` The loaded dump
+fn load_dump(sprites_path: &Path) -> Result<Dump> {
+    let dump = load::load_collections_and_animations(sprites_path)?;
+    for error in &dump.errors {
+        eprintln!("Skipped: {error}");
+    }
+    Ok(dump)
+}
+
+/// Load a dump and find a collection in it by name.
+/// # Arguments
+/// * `sprites_path` - The path to the sprites folder
+/// * `collection_name` - The name of the collection
+/// # Returns
+/// * `Result<Collection>` The found collection
+fn find_collection(sprites_path: &Path, collection_name: &str) -> Result<Collection> {
+    load_dump(sprites_path)?
+        .collections
+        .into_iter()
+        .find(|cln| cln.name == collection_name)
+        .ok_or_else(|| SpritePackerError::CollectionNotFound(collection_name.to_string()))
+}
+
+/// Check a collection, printing every changed sprite.
+/// # Arguments
+/// * `sprites_path` - The path to the sprites folder
+/// * `collection` - The collection to check
+/// * `cache` - The cache of frame hashes of the dump
+/// # Returns
+/// * `Result<bool>` Whether all sprites are identical to their duplicates
+fn run_check(sprites_path: &Path, collection: &Collection, cache: &HashCache) -> Result<bool> {
+    let changed_sprites = check::check(
+        sprites_path,
+        std::slice::from_ref(collection),
+        Some(cache),
+        None,
+        |sprite| {
+            println!("Changed: {}", sprite.path);
+        },
+    )?;
+    Ok(changed_sprites.is_empty())
+}
+
+/// Save the cache of frame hashes of a dump, only warning if it could not be saved since the check itself succeeded.
+/// # Arguments
+/// * `cache` - The cache
+fn save_cache(cache: &HashCache) {
+    if let Err(e) = cache.save() {
+        eprintln!("{e}");
+    }
+}
+
+/// Save the project file of a dump, only warning if it could not be saved since the operation itself succeeded.
+/// # Arguments
+/// * `project` - The project
+/// * `sprites_path` - The path to the sprites folder
+fn save_project(project: &Project, sprites_path: &Path) {
+    if let Err(e) = project.save(sprites_path) {
+        eprintln!("{e}");
+    }
+}
+
+/// Get how to lay sprites out, preferring the layout flags over the project.
+/// # Arguments
+/// * `args` - The layout flags
+/// * `project` - The project of the dump
+/// # Returns
+/// * `Layout` The layout from the flags if `--repack` was given, or else the project's layout
+fn project_layout(args: LayoutArgs, project: &Project) -> Layout {
+    if args.repack {
+        args.into()
+    } else {
+        project.layout.unwrap_or_default()
+    }
+}
+
+/// Print the files and atlas regions that packing a collection would write.
+/// # Arguments
+/// * `report` - The report of the dry run
+fn print_pack_report(report: &PackReport) {
+    println!(
+        "Would save {} ({}x{}, {} pixels changed)",
+        report.atlas_path.display(),
+        report.atlas_width,
+        report.atlas_height,
+        report.changed_pixels
+    );
+    if let Some(sprite_info_path) = &report.sprite_info_path {
+        println!("Would save {}", sprite_info_path.display());
+    }
+    for region in &report.regions {
+        println!(
+            "    {} at {}, {} {}x{} ({} pixels changed)",
+            region.sprite_name,
+            region.rect.x,
+            region.rect.y,
+            region.rect.width,
+            region.rect.height,
+            region.changed_pixels
+        );
+    }
+}
+
+/// Check whether a path given on the command line refers to a backed up file.
+/// # Arguments
+/// * `path` - The path given on the command line
+/// * `original_path` - The path the file was backed up from
+/// # Returns
+/// * `bool` Whether both paths refer to the same file
+fn is_same_file(path: &Path, original_path: &Path) -> bool {
+    path == original_path || path.canonicalize().is_ok_and(|path| path == original_path)
+}
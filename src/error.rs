@@ -0,0 +1,64 @@
+use std::{io, path::PathBuf};
+
+use thiserror::Error;
+
+/// An error raised while loading, checking, replacing or packing sprites.
+#[derive(Debug, Error)]
+pub enum SpritePackerError {
+    #[error("Failed to read {path}: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+    #[error("Failed to parse {path}: {source}")]
+    Json {
+        path: PathBuf,
+        #[source]
+        source: serde_json::Error,
+    },
+    #[error("Failed to open image at {path}: {source}")]
+    OpenImage {
+        path: PathBuf,
+        #[source]
+        source: image::ImageError,
+    },
+    #[error("Failed to save image at {path}: {source}")]
+    SaveImage {
+        path: PathBuf,
+        #[source]
+        source: image::ImageError,
+    },
+    #[error("SpriteInfo.json at {path} has no complete entry at index {index}")]
+    MalformedSpriteInfo { path: PathBuf, index: usize },
+    #[error("Failed to find sprite for frame at {0}")]
+    UnknownFrame(PathBuf),
+    #[error("Failed to parse sprite ID from {0}")]
+    InvalidSpriteId(String),
+    #[error("Trim rect of sprite {0} lies outside its frame image")]
+    TrimOutOfBounds(String),
+    #[error("Sprite {0} does not have the same trimmed size as {1}")]
+    SizeMismatch(String, String),
+    #[error("Collection {0} does not fit into a {1}x{1} atlas")]
+    AtlasTooSmall(String, u32),
+    #[error("Repacking {0} over its original atlas would leave SpriteInfo.json describing the old layout; save it to a separate path instead")]
+    RepackOverOriginal(String),
+    #[error("Collection {0} not found")]
+    CollectionNotFound(String),
+    #[error("Could not choose which of the duplicates {0} to keep")]
+    AmbiguousDuplicates(String),
+    #[error("{0} was changed since it was last written, so it was left as it is")]
+    FileChanged(PathBuf),
+    #[error("The operation was cancelled")]
+    Cancelled,
+    #[error("{0} stopped unexpectedly")]
+    JobPanicked(String),
+    #[cfg(feature = "gui")]
+    #[error("Failed to watch sprites path: {0}")]
+    Watch(#[from] notify::Error),
+    #[cfg(feature = "gui")]
+    #[error("Failed to load or store settings: {0}")]
+    Settings(#[from] confy::ConfyError),
+}
+
+pub type Result<T> = std::result::Result<T, SpritePackerError>;
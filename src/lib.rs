@@ -0,0 +1,33 @@
+//! Loading, checking and packing of sprites dumped by GODump.
+//!
+//! A dump is loaded with [`load_collections_and_animations`], after which each [`Collection`]
+//! can be checked for duplicates that differ with [`check`], fixed with
+//! [`replace_duplicate_sprites`] and packed into an atlas with [`pack_collection`], or
+//! several at once with [`pack_collections`].
+
+pub mod error;
+pub mod packer;
+pub mod tk2d;
+
+pub use error::{Result, SpritePackerError};
+pub use packer::{
+    backup::{list_backups, Backup, BackupFile},
+    cache::HashCache,
+    cancel::CancelToken,
+    check::{check, find_edited_frames, full_check, CheckReport},
+    diff::{diff_sprites, diff_with_atlas, SpriteDiff},
+    history::{FileChange, History, Operation},
+    load::{load_collections_and_animations, load_incrementally, Dump},
+    pack::{pack_collection, pack_collections, repack_collection, Layout},
+    project::{Project, Resolution},
+    replace::replace_duplicate_sprites,
+    resolve::{choose_sources, Conflict, Strategy},
+    trim::{find_exceeding_frames, retrim_collection, ExceedingFrame},
+};
+pub use tk2d::{
+    anim::Animation,
+    clip::Clip,
+    cln::Collection,
+    info::{AnimInfo, SpriteInfo},
+    sprite::{Sprite, SpriteImage},
+};
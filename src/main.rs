@@ -5,7 +5,6 @@ use eframe::egui;
 
 pub mod app;
 pub mod components;
-pub mod tk2d;
 
 fn main() -> Result<(), eframe::Error> {
     let options = eframe::NativeOptions {
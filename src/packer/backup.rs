@@ -0,0 +1,346 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Result, SpritePackerError};
+
+/// The name of the folder in the sprites folder that backups are stored in.
+pub const BACKUPS_DIR_NAME: &str = "0.Backups";
+/// The name of the file in a backup folder that lists the files in the backup.
+const MANIFEST_FILE_NAME: &str = "Backup.json";
+/// The counter that keeps the folders of backups started in the same millisecond apart.
+static NEXT_BACKUP_NUMBER: AtomicUsize = AtomicUsize::new(0);
+
+/// A file that was backed up before being overwritten.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct BackupFile {
+    /// Where the file was backed up from
+    #[serde(rename = "originalPath")]
+    pub original_path: PathBuf,
+    /// The name of the copy of the file in the backup folder
+    #[serde(rename = "backupName")]
+    pub backup_name: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct Manifest {
+    operation: String,
+    timestamp: u64,
+    files: Vec<BackupFile>,
+}
+
+/// A snapshot of every file overwritten by a single operation, such as a replace or a pack.
+#[derive(Debug)]
+pub struct Backup {
+    /// The folder the backup is stored in
+    pub path: PathBuf,
+    /// The operation that overwrote the files
+    pub operation: String,
+    /// When the backup was made, in seconds since the Unix epoch
+    pub timestamp: u64,
+    files: Mutex<Vec<BackupFile>>,
+}
+
+impl Backup {
+    /// Start a new, empty backup in the backups folder of a dump.
+    /// The backup folder is only created once the first file is backed up. Its name is numbered
+    /// so that backups started in the same millisecond never share a folder.
+    /// # Arguments
+    /// * `sprites_path` - The path to the sprites folder
+    /// * `operation` - A short description of the operation, e.g. `Replace Idle-0-12.png`
+    /// # Returns
+    /// * `Backup` The new backup
+    pub fn new(sprites_path: &Path, operation: &str) -> Self {
+        let since_epoch = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        let operation_name = operation.replace(|c: char| !c.is_ascii_alphanumeric(), "_");
+        let path = loop {
+            let number = NEXT_BACKUP_NUMBER.fetch_add(1, Ordering::Relaxed);
+            let folder_name = format!("{}-{}-{}", since_epoch.as_millis(), number, operation_name);
+            let path = sprites_path.join(BACKUPS_DIR_NAME).join(folder_name);
+            if !path.exists() {
+                break path;
+            }
+        };
+        Self {
+            path,
+            operation: operation.to_string(),
+            timestamp: since_epoch.as_secs(),
+            files: Mutex::new(vec![]),
+        }
+    }
+
+    /// Open a backup that was previously saved to disk.
+    /// # Arguments
+    /// * `path` - The folder the backup is stored in
+    /// # Returns
+    /// * `Result<Backup>` The backup
+    pub fn open(path: &Path) -> Result<Self> {
+        let manifest_path = path.join(MANIFEST_FILE_NAME);
+        let manifest_text =
+            fs::read_to_string(&manifest_path).map_err(|source| SpritePackerError::Io {
+                path: manifest_path.clone(),
+                source,
+            })?;
+        let manifest: Manifest =
+            serde_json::from_str(&manifest_text).map_err(|source| SpritePackerError::Json {
+                path: manifest_path.clone(),
+                source,
+            })?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            operation: manifest.operation,
+            timestamp: manifest.timestamp,
+            files: Mutex::new(manifest.files),
+        })
+    }
+
+    /// Get the files in the backup.
+    /// # Returns
+    /// * `Vec<BackupFile>` The backed up files, in the order they were backed up
+    pub fn files(&self) -> Vec<BackupFile> {
+        self.files.lock().unwrap().clone()
+    }
+
+    /// Copy a file into the backup before it is overwritten.
+    /// Files that do not exist yet or were already backed up are skipped.
+    /// # Arguments
+    /// * `path` - The path to the file
+    /// # Returns
+    /// * `Result<()>` Whether the file was backed up
+    pub fn snapshot(&self, path: &Path) -> Result<()> {
+        if !path.is_file() {
+            return Ok(());
+        }
+        let original_path = path
+            .canonicalize()
+            .map_err(|source| SpritePackerError::Io {
+                path: path.to_path_buf(),
+                source,
+            })?;
+
+        let mut files = self.files.lock().unwrap();
+        if files.iter().any(|file| file.original_path == original_path) {
+            return Ok(());
+        }
+
+        fs::create_dir_all(&self.path).map_err(|source| SpritePackerError::Io {
+            path: self.path.clone(),
+            source,
+        })?;
+        let extension = original_path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or_default();
+        let backup_name = format!("{}.{}", files.len(), extension);
+        let backup_path = self.path.join(&backup_name);
+        fs::copy(&original_path, &backup_path).map_err(|source| SpritePackerError::Io {
+            path: backup_path.clone(),
+            source,
+        })?;
+        files.push(BackupFile {
+            original_path,
+            backup_name,
+        });
+
+        self.save_manifest(&files)
+    }
+
+    /// Copy a backed up file back to where it was backed up from.
+    /// # Arguments
+    /// * `file` - The file to restore
+    /// # Returns
+    /// * `Result<()>` Whether the file was restored
+    pub fn restore_file(&self, file: &BackupFile) -> Result<()> {
+        if let Some(parent) = file.original_path.parent() {
+            fs::create_dir_all(parent).map_err(|source| SpritePackerError::Io {
+                path: parent.to_path_buf(),
+                source,
+            })?;
+        }
+        let backup_path = self.path.join(&file.backup_name);
+        fs::copy(&backup_path, &file.original_path).map_err(|source| SpritePackerError::Io {
+            path: backup_path.clone(),
+            source,
+        })?;
+        Ok(())
+    }
+
+    /// Copy every backed up file back to where it was backed up from.
+    /// # Returns
+    /// * `Result<()>` Whether all files were restored
+    pub fn restore(&self) -> Result<()> {
+        for file in self.files() {
+            self.restore_file(&file)?;
+        }
+        Ok(())
+    }
+
+    /// Save the list of backed up files next to them.
+    /// # Arguments
+    /// * `files` - The backed up files
+    /// # Returns
+    /// * `Result<()>` Whether the list was saved
+    fn save_manifest(&self, files: &[BackupFile]) -> Result<()> {
+        let manifest_path = self.path.join(MANIFEST_FILE_NAME);
+        let manifest = Manifest {
+            operation: self.operation.clone(),
+            timestamp: self.timestamp,
+            files: files.to_vec(),
+        };
+        let manifest_text =
+            serde_json::to_string_pretty(&manifest).map_err(|source| SpritePackerError::Json {
+                path: manifest_path.clone(),
+                source,
+            })?;
+        fs::write(&manifest_path, manifest_text).map_err(|source| SpritePackerError::Io {
+            path: manifest_path.clone(),
+            source,
+        })
+    }
+}
+
+/// List the backups of a dump, newest first.
+/// # Arguments
+/// * `sprites_path` - The path to the sprites folder
+/// # Returns
+/// * `Result<Vec<Backup>>` The backups, or an empty list if nothing has been backed up yet
+pub fn list_backups(sprites_path: &Path) -> Result<Vec<Backup>> {
+    let backups_path = sprites_path.join(BACKUPS_DIR_NAME);
+    let Ok(backup_paths) = fs::read_dir(&backups_path) else {
+        return Ok(vec![]);
+    };
+
+    let mut backups = vec![];
+    for backup_entry in backup_paths.flatten() {
+        if backup_entry.path().join(MANIFEST_FILE_NAME).is_file() {
+            backups.push(Backup::open(&backup_entry.path())?);
+        }
+    }
+    backups.sort_by(|backup1, backup2| {
+        backup2
+            .timestamp
+            .cmp(&backup1.timestamp)
+            .then_with(|| backup2.path.cmp(&backup1.path))
+    });
+
+    Ok(backups)
+}
+
+/// Format a timestamp as a date and time in UTC.
+/// # Arguments
+/// * `timestamp` - The number of seconds since the Unix epoch
+/// # Returns
+/// * `String` The date and time, e.g. `2024-01-25 13:37:00`
+pub fn format_timestamp(timestamp: u64) -> String {
+    let days = (timestamp / 86400) as i64;
+    let seconds = timestamp % 86400;
+
+    // Convert days since the epoch to a civil date, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Create an empty sprites folder in the temporary directory for a test.
+    fn test_folder(name: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("spritepacker-backup-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        path.canonicalize().unwrap()
+    }
+
+    #[test]
+    fn restore_brings_back_overwritten_files() {
+        let folder = test_folder("restore");
+        let frame_path = folder.join("Knight").join("Idle-0-0.png");
+        let atlas_path = folder.join("atlas.png");
+        fs::create_dir_all(frame_path.parent().unwrap()).unwrap();
+        fs::write(&frame_path, "frame").unwrap();
+        fs::write(&atlas_path, "atlas").unwrap();
+
+        let backup = Backup::new(&folder, "Replace Idle-0-0.png");
+        backup.snapshot(&frame_path).unwrap();
+        backup.snapshot(&atlas_path).unwrap();
+        backup.snapshot(&frame_path).unwrap();
+        backup.snapshot(&folder.join("missing.png")).unwrap();
+        assert_eq!(backup.files().len(), 2);
+
+        fs::write(&frame_path, "edited frame").unwrap();
+        fs::remove_file(&atlas_path).unwrap();
+
+        let backups = list_backups(&folder).unwrap();
+        assert_eq!(backups.len(), 1);
+        assert_eq!(backups[0].operation, "Replace Idle-0-0.png");
+        assert_eq!(backups[0].files(), backup.files());
+        backups[0].restore().unwrap();
+
+        assert_eq!(fs::read_to_string(&frame_path).unwrap(), "frame");
+        assert_eq!(fs::read_to_string(&atlas_path).unwrap(), "atlas");
+        fs::remove_dir_all(folder).unwrap();
+    }
+
+    #[test]
+    fn backups_are_listed_newest_first() {
+        let folder = test_folder("list");
+        assert!(list_backups(&folder).unwrap().is_empty());
+
+        let file_path = folder.join("frame.png");
+        fs::write(&file_path, "frame").unwrap();
+        let operations = ["Pack", "Replace", "Pack All"];
+        for operation in operations {
+            Backup::new(&folder, operation)
+                .snapshot(&file_path)
+                .unwrap();
+        }
+
+        let listed_operations: Vec<String> = list_backups(&folder)
+            .unwrap()
+            .into_iter()
+            .map(|backup| backup.operation)
+            .collect();
+        assert_eq!(listed_operations, ["Pack All", "Replace", "Pack"]);
+        fs::remove_dir_all(folder).unwrap();
+    }
+
+    #[test]
+    fn timestamp_is_formatted_in_utc() {
+        assert_eq!(format_timestamp(0), "1970-01-01 00:00:00");
+        assert_eq!(format_timestamp(1706189820), "2024-01-25 13:37:00");
+    }
+}
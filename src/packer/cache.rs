@@ -0,0 +1,411 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, UNIX_EPOCH},
+};
+
+use image::DynamicImage;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{Result, SpritePackerError},
+    packer::resolve_path,
+    tk2d::{
+        cln::Collection,
+        sprite::{Sprite, SpriteImage},
+    },
+};
+
+/// The name of the file in the sprites folder that the cache is stored in.
+pub const CACHE_FILE_NAME: &str = "0.Cache.json";
+
+/// What is known about a frame image from the last time it was decoded.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct CacheEntry {
+    /// When the file was last modified, since the Unix epoch
+    pub modified: Duration,
+    /// The size of the file in bytes
+    pub size: u64,
+    /// The width of the frame image
+    pub width: u32,
+    /// The height of the frame image
+    pub height: u32,
+    /// The trim rect the hash was computed over, as `[xr, yr, width, height]`
+    pub trim: [i32; 4],
+    /// The hash of the trimmed frame
+    pub hash: u64,
+    /// The trim rect fitted to the opaque pixels of the frame, as `[xr, yr, width, height]`,
+    /// which is empty if the frame is fully transparent
+    #[serde(default)]
+    pub opaque_trim: Option<[i32; 4]>,
+    /// How the trimmed frame compared with the atlas it was dumped from, if it was compared with one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub atlas_match: Option<AtlasMatch>,
+}
+
+/// Whether a trimmed frame matched the region of an atlas it is packed into.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct AtlasMatch {
+    /// When the atlas was last modified, since the Unix epoch
+    pub modified: Duration,
+    /// The size of the atlas in bytes
+    pub size: u64,
+    /// Where the sprite is packed into the atlas, as `[x, y, flipped]`
+    pub position: [i32; 3],
+    /// Whether every pixel of the trimmed frame matched the atlas
+    pub matches: bool,
+}
+
+/// The original atlas of a collection, only decoded once a frame that is not cached has to be compared with it.
+#[derive(Debug)]
+pub struct CachedAtlas {
+    path: PathBuf,
+    modified: Option<Duration>,
+    size: u64,
+    image: Mutex<Option<Arc<DynamicImage>>>,
+}
+
+impl CachedAtlas {
+    /// Prepare the atlas of a collection to be compared with its frames, without decoding it yet.
+    /// # Arguments
+    /// * `collection` - The collection
+    /// # Returns
+    /// * `CachedAtlas` The atlas
+    pub fn new(collection: &Collection) -> Self {
+        let metadata = fs::metadata(&collection.path).ok();
+        Self {
+            path: collection.path.clone(),
+            modified: metadata
+                .as_ref()
+                .and_then(|metadata| metadata.modified().ok())
+                .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok()),
+            size: metadata.map_or(0, |metadata| metadata.len()),
+            image: Mutex::new(None),
+        }
+    }
+
+    /// Decode the atlas, or get it if it was already decoded.
+    /// # Returns
+    /// * `Result<Arc<DynamicImage>>` The atlas image
+    fn image(&self) -> Result<Arc<DynamicImage>> {
+        let mut image = self.image.lock().unwrap();
+        if let Some(image) = image.as_ref() {
+            return Ok(image.clone());
+        }
+        let atlas = image::open(&self.path).map_err(|source| SpritePackerError::OpenImage {
+            path: self.path.clone(),
+            source,
+        })?;
+        Ok(image.insert(Arc::new(atlas)).clone())
+    }
+
+    /// Check whether a cached comparison with this atlas still holds for a sprite.
+    /// # Arguments
+    /// * `sprite` - The sprite that was compared
+    /// * `atlas_match` - The cached comparison
+    /// # Returns
+    /// * `bool` Whether the atlas and the sprite's place in it are unchanged since
+    fn is_compared(&self, sprite: &Sprite, atlas_match: &AtlasMatch) -> bool {
+        self.modified == Some(atlas_match.modified)
+            && self.size == atlas_match.size
+            && atlas_match.position == [sprite.x, sprite.y, sprite.flipped as i32]
+    }
+
+    /// Compare a decoded frame with the region of the atlas it is packed into.
+    /// # Arguments
+    /// * `sprite_image` - The decoded frame
+    /// # Returns
+    /// * `Result<AtlasMatch>` Whether the frame matches, along with what it was compared with
+    fn compare(&self, sprite_image: &SpriteImage) -> Result<AtlasMatch> {
+        let sprite = &sprite_image.sprite;
+        Ok(AtlasMatch {
+            modified: self.modified.unwrap_or_default(),
+            size: self.size,
+            position: [sprite.x, sprite.y, sprite.flipped as i32],
+            matches: sprite_image.matches_atlas(&*self.image()?),
+        })
+    }
+}
+
+/// Decode a sprite's frame and work out everything that is cached about it, in a single pass over the file.
+/// # Arguments
+/// * `sprites_path` - The path to the sprites folder
+/// * `sprite` - The sprite
+/// * `atlas` - The atlas to compare the frame with, if any
+/// # Returns
+/// * `Result<CacheEntry>` What is known about the frame, with its modification time and size left unset
+fn decode_entry(
+    sprites_path: &Path,
+    sprite: &Sprite,
+    atlas: Option<&CachedAtlas>,
+) -> Result<CacheEntry> {
+    let sprite_image = SpriteImage::open(sprites_path, sprite)?;
+    let opaque_trim = sprite_image.opaque_trim().map_or([0; 4], |opaque_sprite| {
+        [
+            opaque_sprite.xr,
+            opaque_sprite.yr,
+            opaque_sprite.width,
+            opaque_sprite.height,
+        ]
+    });
+    Ok(CacheEntry {
+        width: sprite_image.image.width(),
+        height: sprite_image.image.height(),
+        trim: [sprite.xr, sprite.yr, sprite.width, sprite.height],
+        hash: sprite_image.trim_hash(),
+        opaque_trim: Some(opaque_trim),
+        atlas_match: atlas
+            .map(|atlas| atlas.compare(&sprite_image))
+            .transpose()?,
+        ..Default::default()
+    })
+}
+
+/// Get what is known about a sprite's frame, from a cache if there is one or else by decoding it.
+/// # Arguments
+/// * `sprites_path` - The path to the sprites folder
+/// * `sprite` - The sprite
+/// * `cache` - The cache to look the frame up in, if any
+/// * `atlas` - The atlas to compare the frame with, if any
+/// # Returns
+/// * `Result<CacheEntry>` What is known about the frame
+pub fn frame_entry(
+    sprites_path: &Path,
+    sprite: &Sprite,
+    cache: Option<&HashCache>,
+    atlas: Option<&CachedAtlas>,
+) -> Result<CacheEntry> {
+    match cache {
+        Some(cache) => cache.entry(sprites_path, sprite, atlas),
+        None => decode_entry(sprites_path, sprite, atlas),
+    }
+}
+
+/// A cache of the trimmed frame hashes of a dump, along with their opaque bounds and how they compare with their atlas, persisted between runs.
+/// Entries are keyed by the path of the frame image and only used while its modification time, size and trim rect are unchanged, so only new or edited frames are decoded again.
+#[derive(Debug, Default)]
+pub struct HashCache {
+    /// The file the cache is stored in
+    pub path: PathBuf,
+    entries: Mutex<HashMap<PathBuf, CacheEntry>>,
+    dirty: AtomicBool,
+}
+
+impl HashCache {
+    /// Open the cache of a dump.
+    /// A missing or unreadable cache file is treated as an empty cache.
+    /// # Arguments
+    /// * `sprites_path` - The path to the sprites folder
+    /// # Returns
+    /// * `HashCache` The cache
+    pub fn open(sprites_path: &Path) -> Self {
+        let path = sprites_path.join(CACHE_FILE_NAME);
+        let entries = fs::read_to_string(&path)
+            .ok()
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default();
+        Self {
+            path,
+            entries: Mutex::new(entries),
+            dirty: AtomicBool::new(false),
+        }
+    }
+
+    /// Get what is known about a sprite's frame, decoding the frame image only if it is not cached, has changed,
+    /// or has not been compared with the given atlas since either of them changed.
+    /// # Arguments
+    /// * `sprites_path` - The path to the sprites folder
+    /// * `sprite` - The sprite
+    /// * `atlas` - The atlas to compare the frame with, if any
+    /// # Returns
+    /// * `Result<CacheEntry>` What is known about the frame
+    pub fn entry(
+        &self,
+        sprites_path: &Path,
+        sprite: &Sprite,
+        atlas: Option<&CachedAtlas>,
+    ) -> Result<CacheEntry> {
+        let path = resolve_path(sprites_path, &sprite.path);
+        let metadata = fs::metadata(&path).map_err(|source| SpritePackerError::Io {
+            path: path.clone(),
+            source,
+        })?;
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok());
+        let trim = [sprite.xr, sprite.yr, sprite.width, sprite.height];
+        if let Some(modified) = modified {
+            if let Some(entry) = self.entries.lock().unwrap().get(&path) {
+                let is_compared = atlas.map_or(true, |atlas| {
+                    entry
+                        .atlas_match
+                        .as_ref()
+                        .is_some_and(|atlas_match| atlas.is_compared(sprite, atlas_match))
+                });
+                if entry.modified == modified
+                    && entry.size == metadata.len()
+                    && entry.trim == trim
+                    && entry.opaque_trim.is_some()
+                    && is_compared
+                {
+                    return Ok(entry.clone());
+                }
+            }
+        }
+
+        let mut entry = decode_entry(sprites_path, sprite, atlas)?;
+        if let Some(modified) = modified {
+            entry.modified = modified;
+            entry.size = metadata.len();
+            self.entries.lock().unwrap().insert(path, entry.clone());
+            self.dirty.store(true, Ordering::Relaxed);
+        }
+
+        Ok(entry)
+    }
+
+    /// Forget what is cached about a file, e.g. because it was reported as changed.
+    /// # Arguments
+    /// * `path` - The path to the frame image
+    pub fn invalidate(&self, path: &Path) {
+        if self.entries.lock().unwrap().remove(path).is_some() {
+            self.dirty.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Write the cache to its file if anything was added or removed since it was opened or last saved.
+    /// # Returns
+    /// * `Result<()>` Whether the cache was saved
+    pub fn save(&self) -> Result<()> {
+        if !self.dirty.swap(false, Ordering::Relaxed) {
+            return Ok(());
+        }
+        let text = serde_json::to_string(&*self.entries.lock().unwrap()).map_err(|source| {
+            SpritePackerError::Json {
+                path: self.path.clone(),
+                source,
+            }
+        })?;
+        fs::write(&self.path, text).map_err(|source| {
+            self.dirty.store(true, Ordering::Relaxed);
+            SpritePackerError::Io {
+                path: self.path.clone(),
+                source,
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs::File, time::SystemTime};
+
+    use image::{Rgba, RgbaImage};
+
+    use super::*;
+
+    /// A hash that no frame in these tests has, to tell an entry served from the cache apart from a decoded one.
+    const STALE_HASH: u64 = 0;
+
+    /// Create an empty folder in the temporary directory with a single 2x2 frame in it, and return the sprite of the frame.
+    fn test_frame(name: &str) -> (PathBuf, Sprite) {
+        let folder =
+            std::env::temp_dir().join(format!("spritepacker-cache-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&folder);
+        fs::create_dir_all(&folder).unwrap();
+        let sprite = Sprite {
+            width: 2,
+            height: 2,
+            path: "frame.png".to_string(),
+            ..Default::default()
+        };
+        RgbaImage::from_pixel(2, 2, Rgba([255, 0, 0, 255]))
+            .save(folder.join(&sprite.path))
+            .unwrap();
+        (folder, sprite)
+    }
+
+    /// Cache a frame and then replace its cached hash, so that the next lookup shows whether it was decoded again.
+    fn cache_stale_entry(cache: &HashCache, folder: &Path, sprite: &Sprite) {
+        cache.entry(folder, sprite, None).unwrap();
+        for entry in cache.entries.lock().unwrap().values_mut() {
+            entry.hash = STALE_HASH;
+        }
+    }
+
+    #[test]
+    fn unchanged_frame_is_served_from_cache() {
+        let (folder, sprite) = test_frame("unchanged");
+        let cache = HashCache::open(&folder);
+        cache_stale_entry(&cache, &folder, &sprite);
+
+        assert_eq!(
+            cache.entry(&folder, &sprite, None).unwrap().hash,
+            STALE_HASH
+        );
+        fs::remove_dir_all(folder).unwrap();
+    }
+
+    #[test]
+    fn frame_with_new_modification_time_is_decoded_again() {
+        let (folder, sprite) = test_frame("modified");
+        let cache = HashCache::open(&folder);
+        cache_stale_entry(&cache, &folder, &sprite);
+
+        let file = File::options()
+            .write(true)
+            .open(folder.join(&sprite.path))
+            .unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(60))
+            .unwrap();
+        assert_ne!(
+            cache.entry(&folder, &sprite, None).unwrap().hash,
+            STALE_HASH
+        );
+        fs::remove_dir_all(folder).unwrap();
+    }
+
+    #[test]
+    fn frame_with_new_size_is_decoded_again() {
+        let (folder, sprite) = test_frame("resized");
+        let cache = HashCache::open(&folder);
+        cache_stale_entry(&cache, &folder, &sprite);
+
+        let path = folder.join(&sprite.path);
+        let modified = fs::metadata(&path).unwrap().modified().unwrap();
+        RgbaImage::from_pixel(4, 4, Rgba([255, 0, 0, 255]))
+            .save(&path)
+            .unwrap();
+        File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+        assert_ne!(
+            cache.entry(&folder, &sprite, None).unwrap().hash,
+            STALE_HASH
+        );
+        fs::remove_dir_all(folder).unwrap();
+    }
+
+    #[test]
+    fn invalidated_frame_is_decoded_again_and_saved() {
+        let (folder, sprite) = test_frame("invalidated");
+        let cache = HashCache::open(&folder);
+        cache.entry(&folder, &sprite, None).unwrap();
+        cache.save().unwrap();
+        assert_eq!(HashCache::open(&folder).entries.lock().unwrap().len(), 1);
+
+        cache.invalidate(&folder.join(&sprite.path));
+        cache.save().unwrap();
+        assert!(HashCache::open(&folder).entries.lock().unwrap().is_empty());
+        fs::remove_dir_all(folder).unwrap();
+    }
+}
@@ -0,0 +1,52 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use crate::error::{Result, SpritePackerError};
+
+/// A flag shared with an operation running in the background, used to ask it to stop early.
+/// Clones of a token share the same flag.
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    /// Create a token that has not been cancelled.
+    /// # Returns
+    /// * `CancelToken` The new token
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ask every operation sharing this token to stop.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Check whether the token has been cancelled.
+    /// # Returns
+    /// * `bool` Whether the operation should stop
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Fail with `SpritePackerError::Cancelled` if the token has been cancelled.
+    /// # Returns
+    /// * `Result<()>` Whether the operation may go on
+    pub fn check(&self) -> Result<()> {
+        if self.is_cancelled() {
+            Err(SpritePackerError::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Fail with `SpritePackerError::Cancelled` if an optional token has been cancelled.
+/// # Arguments
+/// * `cancel` - The token, if any
+/// # Returns
+/// * `Result<()>` Whether the operation may go on
+pub fn check_cancelled(cancel: Option<&CancelToken>) -> Result<()> {
+    cancel.map_or(Ok(()), CancelToken::check)
+}
@@ -0,0 +1,449 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::Path,
+};
+
+use image::GenericImageView;
+use rayon::iter::{IntoParallelIterator, IntoParallelRefIterator, ParallelIterator};
+
+use crate::{
+    error::{Result, SpritePackerError},
+    packer::{
+        cache::{frame_entry, CacheEntry, CachedAtlas, HashCache},
+        cancel::{check_cancelled, CancelToken},
+        trim::{exceeding_frame, ExceedingFrame},
+    },
+    tk2d::{
+        cln::Collection,
+        sprite::{Sprite, SpriteImage},
+    },
+};
+
+/// What checking a collection found.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CheckReport {
+    /// The name of the collection that was checked
+    pub collection_name: String,
+    /// The sprites whose duplicates are not all identical, in the order they appear in the collection
+    pub changed_sprites: Vec<Sprite>,
+    /// The frames with opaque pixels outside their trim rect
+    pub exceeding_frames: Vec<ExceedingFrame>,
+    /// The frames that differ from the atlas they were dumped from
+    pub edited_frames: Vec<Sprite>,
+}
+
+/// Check whether any sprites and their duplicates are not identical.
+/// Collections are checked in parallel, and each frame is decoded only once.
+/// # Arguments
+/// * `sprites_path` - The path to the sprites folder
+/// * `collections` - The collections to check
+/// * `cache` - The cache to look up the hashes of unchanged frames in, if any
+/// * `cancel` - The token to stop checking early with, if any
+/// * `on_changed` - Called with each changed sprite as soon as its collection has been checked
+/// # Returns
+/// * `Result<Vec<Sprite>>` All sprites whose duplicates are not all identical, in the order of their collections
+pub fn check(
+    sprites_path: &Path,
+    collections: &[Collection],
+    cache: Option<&HashCache>,
+    cancel: Option<&CancelToken>,
+    on_changed: impl Fn(&Sprite) + Sync,
+) -> Result<Vec<Sprite>> {
+    let problem_sprites = collections
+        .par_iter()
+        .map(|collection| {
+            let changed_sprites = check_collection(sprites_path, collection, cache, cancel)?;
+            for sprite in &changed_sprites {
+                on_changed(sprite);
+            }
+            Ok(changed_sprites)
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(problem_sprites.into_iter().flatten().collect())
+}
+
+/// Find the sprites of a collection whose duplicates are not all identical.
+/// Every frame that has a duplicate is decoded once in parallel and its trimmed region hashed, so duplicates are grouped by hash rather than compared pair by pair.
+/// Frames with the same hash are then compared pixel by pixel before they count as identical.
+/// # Arguments
+/// * `sprites_path` - The path to the sprites folder
+/// * `collection` - The collection to check
+/// * `cache` - The cache to look up the hashes of unchanged frames in, if any
+/// * `cancel` - The token to stop checking early with, if any
+/// # Returns
+/// * `Result<Vec<Sprite>>` The changed sprites, in the order they appear in the collection
+fn check_collection(
+    sprites_path: &Path,
+    collection: &Collection,
+    cache: Option<&HashCache>,
+    cancel: Option<&CancelToken>,
+) -> Result<Vec<Sprite>> {
+    let mut id_counts = HashMap::<u32, usize>::new();
+    for sprite in &collection.sprites {
+        *id_counts.entry(sprite.id).or_default() += 1;
+    }
+    let hashed_sprites = collection
+        .sprites
+        .par_iter()
+        .filter(|sprite| id_counts[&sprite.id] > 1)
+        .map(|sprite| {
+            check_cancelled(cancel)?;
+            let hash = frame_entry(sprites_path, sprite, cache, None)?.hash;
+            Ok((sprite, hash))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    group_changed_sprites(sprites_path, hashed_sprites)
+}
+
+/// Find the sprites whose duplicates are not all identical.
+/// # Arguments
+/// * `sprites_path` - The path to the sprites folder
+/// * `hashed_sprites` - The sprites of a collection along with the hashes of their trimmed frames
+/// # Returns
+/// * `Result<Vec<Sprite>>` The changed sprites, in the order they were given
+fn group_changed_sprites(
+    sprites_path: &Path,
+    hashed_sprites: Vec<(&Sprite, u64)>,
+) -> Result<Vec<Sprite>> {
+    let mut duplicates = HashMap::<u32, Vec<(&Sprite, u64)>>::new();
+    for (sprite, hash) in &hashed_sprites {
+        duplicates
+            .entry(sprite.id)
+            .or_default()
+            .push((sprite, *hash));
+    }
+    let changed_ids = duplicates
+        .into_par_iter()
+        .map(|(id, duplicates)| {
+            let groups = group_identical_frames(sprites_path, &duplicates)?;
+            Ok((groups.len() > 1).then_some(id))
+        })
+        .collect::<Result<Vec<_>>>()?
+        .into_iter()
+        .flatten()
+        .collect::<HashSet<_>>();
+
+    Ok(hashed_sprites
+        .into_iter()
+        .filter(|(sprite, _)| changed_ids.contains(&sprite.id))
+        .map(|(sprite, _)| sprite.clone())
+        .collect())
+}
+
+/// Split sprites into groups whose trimmed frames are identical.
+/// Sprites are grouped by the hashes of their frames first, and the frames in each group are then compared pixel by pixel,
+/// so two frames are never taken to be identical because of a hash collision.
+/// # Arguments
+/// * `sprites_path` - The path to the sprites folder
+/// * `hashed_sprites` - The sprites along with the hashes of their trimmed frames
+/// # Returns
+/// * `Result<Vec<Vec<&Sprite>>>` The groups of sprites with identical frames, in the order their first sprites were given
+pub fn group_identical_frames<'a>(
+    sprites_path: &Path,
+    hashed_sprites: &[(&'a Sprite, u64)],
+) -> Result<Vec<Vec<&'a Sprite>>> {
+    let mut hash_groups: Vec<(u64, Vec<&Sprite>)> = vec![];
+    for (sprite, hash) in hashed_sprites {
+        match hash_groups
+            .iter_mut()
+            .find(|(group_hash, _)| group_hash == hash)
+        {
+            Some((_, group)) => group.push(sprite),
+            None => hash_groups.push((*hash, vec![sprite])),
+        }
+    }
+
+    let mut groups = vec![];
+    for (_, hash_group) in hash_groups {
+        groups.extend(split_identical_frames(sprites_path, hash_group)?);
+    }
+    Ok(groups)
+}
+
+/// Split sprites whose frames have the same hash into groups whose trimmed frames have the same pixels.
+/// # Arguments
+/// * `sprites_path` - The path to the sprites folder
+/// * `sprites` - The sprites with the same hash
+/// # Returns
+/// * `Result<Vec<Vec<&Sprite>>>` The groups of sprites with identical frames, which is a single group unless the hash collided
+fn split_identical_frames<'a>(
+    sprites_path: &Path,
+    sprites: Vec<&'a Sprite>,
+) -> Result<Vec<Vec<&'a Sprite>>> {
+    if sprites.len() < 2 {
+        return Ok(vec![sprites]);
+    }
+    let sprite_images = sprites
+        .par_iter()
+        .map(|sprite| SpriteImage::open(sprites_path, sprite))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut groups: Vec<(&SpriteImage, Vec<&Sprite>)> = vec![];
+    for (sprite, sprite_image) in sprites.into_iter().zip(&sprite_images) {
+        match groups
+            .iter_mut()
+            .find(|(group_image, _)| same_trimmed_pixels(group_image, sprite_image))
+        {
+            Some((_, group)) => group.push(sprite),
+            None => groups.push((sprite_image, vec![sprite])),
+        }
+    }
+    Ok(groups.into_iter().map(|(_, group)| group).collect())
+}
+
+/// Check whether the trimmed regions of two frames have the same size and pixels.
+/// # Arguments
+/// * `sprite_image1` - The first frame
+/// * `sprite_image2` - The second frame
+/// # Returns
+/// * `bool` Whether the trimmed frames are identical
+fn same_trimmed_pixels(sprite_image1: &SpriteImage, sprite_image2: &SpriteImage) -> bool {
+    let frame1 = sprite_image1.trim();
+    let frame2 = sprite_image2.trim();
+    frame1.dimensions() == frame2.dimensions()
+        && frame1
+            .pixels()
+            .zip(frame2.pixels())
+            .all(|((_, _, pixel1), (_, _, pixel2))| pixel1 == pixel2)
+}
+
+/// Check collections for changed sprites, for frames with opaque pixels outside their trim rect and for frames
+/// that differ from the atlas they were dumped from, all at once.
+/// Each frame is decoded at most once for all three, and not at all if it is cached and unchanged.
+/// # Arguments
+/// * `sprites_path` - The path to the sprites folder
+/// * `collections` - The collections to check
+/// * `cache` - The cache to look up unchanged frames in and to store what is found about the others, if any
+/// * `cancel` - The token to stop checking early with, if any
+/// * `on_checked` - Called with what was found in each collection as soon as it has been checked
+/// # Returns
+/// * `Result<Vec<CheckReport>>` What was found in each collection, in the same order as `collections`
+pub fn full_check(
+    sprites_path: &Path,
+    collections: &[Collection],
+    cache: Option<&HashCache>,
+    cancel: Option<&CancelToken>,
+    on_checked: impl Fn(&CheckReport) + Sync,
+) -> Result<Vec<CheckReport>> {
+    collections
+        .par_iter()
+        .map(|collection| {
+            let report = full_check_collection(sprites_path, collection, cache, cancel)?;
+            on_checked(&report);
+            Ok(report)
+        })
+        .collect()
+}
+
+/// Check a collection for changed sprites, clipped frames and edited frames, decoding each frame at most once.
+/// # Arguments
+/// * `sprites_path` - The path to the sprites folder
+/// * `collection` - The collection to check
+/// * `cache` - The cache to look up unchanged frames in, if any
+/// * `cancel` - The token to stop checking early with, if any
+/// # Returns
+/// * `Result<CheckReport>` What was found in the collection
+fn full_check_collection(
+    sprites_path: &Path,
+    collection: &Collection,
+    cache: Option<&HashCache>,
+    cancel: Option<&CancelToken>,
+) -> Result<CheckReport> {
+    let atlas = CachedAtlas::new(collection);
+    let entries = collection
+        .sprites
+        .par_iter()
+        .map(|sprite| {
+            check_cancelled(cancel)?;
+            Ok((
+                sprite,
+                frame_entry(sprites_path, sprite, cache, Some(&atlas))?,
+            ))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(CheckReport {
+        collection_name: collection.name.clone(),
+        changed_sprites: group_changed_sprites(
+            sprites_path,
+            entries
+                .iter()
+                .map(|(sprite, entry)| (*sprite, entry.hash))
+                .collect(),
+        )?,
+        exceeding_frames: entries
+            .iter()
+            .filter_map(|(sprite, entry)| exceeding_frame(sprite, entry))
+            .collect(),
+        edited_frames: entries
+            .iter()
+            .filter(|(_, entry)| is_edited(entry))
+            .map(|(sprite, _)| (*sprite).clone())
+            .collect(),
+    })
+}
+
+/// Find the frames of a collection that differ from the region of the atlas they were dumped from.
+/// Unlike `check`, this finds edited frames even if they have no duplicates.
+/// # Arguments
+/// * `sprites_path` - The path to the sprites folder
+/// * `collection` - The collection to check
+/// * `cache` - The cache to look up how unchanged frames compared with the atlas in, if any
+/// # Returns
+/// * `Result<Vec<Sprite>>` The frames that no longer match the collection's atlas
+pub fn find_edited_frames(
+    sprites_path: &Path,
+    collection: &Collection,
+    cache: Option<&HashCache>,
+) -> Result<Vec<Sprite>> {
+    // The atlas is only decoded if a frame has not been compared with it yet
+    let atlas = CachedAtlas::new(collection);
+    let edited_frames = collection
+        .sprites
+        .par_iter()
+        .map(|sprite| {
+            let entry = frame_entry(sprites_path, sprite, cache, Some(&atlas))?;
+            Ok(is_edited(&entry).then(|| sprite.clone()))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(edited_frames.into_iter().flatten().collect())
+}
+
+/// Check whether what is known about a frame shows that it differs from the atlas it was dumped from.
+/// # Arguments
+/// * `entry` - What is known about the frame
+/// # Returns
+/// * `bool` Whether the frame was compared with the atlas and did not match it
+pub fn is_edited(entry: &CacheEntry) -> bool {
+    entry
+        .atlas_match
+        .as_ref()
+        .is_some_and(|atlas_match| !atlas_match.matches)
+}
+
+/// Parse the sprite ID from the end of a frame's file name.
+/// # Arguments
+/// * `sprite_name` - The file name of the frame, e.g. `Idle-0-12.png`
+/// # Returns
+/// * `Result<u32>` The sprite ID
+pub fn parse_sprite_id(sprite_name: &str) -> Result<u32> {
+    let sprite_data = sprite_name.split('-').collect::<Vec<&str>>();
+    let sprite_id_string = sprite_data[sprite_data.len() - 1].replace(".png", "");
+    sprite_id_string
+        .parse::<u32>()
+        .map_err(|_| SpritePackerError::InvalidSpriteId(sprite_name.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use image::{DynamicImage, Rgba, RgbaImage};
+
+    use super::*;
+
+    /// Create an empty folder in the temporary directory for a test to write frames to.
+    fn test_folder(name: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("spritepacker-check-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        path
+    }
+
+    /// Save a 4x4 frame with a 2x2 trim rect in its middle and return its sprite.
+    /// The trimmed pixels are filled with `color`, and the padding around them with `padding`.
+    fn save_frame(
+        folder: &Path,
+        name: &str,
+        id: u32,
+        color: Rgba<u8>,
+        padding: Rgba<u8>,
+    ) -> Sprite {
+        let image = RgbaImage::from_fn(4, 4, |x, y| {
+            if (1..3).contains(&x) && (1..3).contains(&y) {
+                color
+            } else {
+                padding
+            }
+        });
+        let path = format!("{name}.png");
+        DynamicImage::ImageRgba8(image)
+            .save(folder.join(&path))
+            .unwrap();
+        Sprite {
+            id,
+            xr: 1,
+            yr: 1,
+            width: 2,
+            height: 2,
+            name: name.to_string(),
+            path,
+            ..Default::default()
+        }
+    }
+
+    /// Open a frame and hash its trimmed region.
+    fn trim_hash(folder: &Path, sprite: &Sprite) -> u64 {
+        SpriteImage::open(folder, sprite).unwrap().trim_hash()
+    }
+
+    #[test]
+    fn trim_hash_ignores_pixels_outside_trim_rect() {
+        let folder = test_folder("hash");
+        let red = Rgba([255, 0, 0, 255]);
+        let sprite1 = save_frame(&folder, "a", 0, red, Rgba([0, 0, 0, 0]));
+        let sprite2 = save_frame(&folder, "b", 0, red, Rgba([0, 255, 0, 255]));
+        let sprite3 = save_frame(&folder, "c", 0, Rgba([255, 0, 0, 254]), Rgba([0, 0, 0, 0]));
+
+        assert_eq!(trim_hash(&folder, &sprite1), trim_hash(&folder, &sprite2));
+        assert_ne!(trim_hash(&folder, &sprite1), trim_hash(&folder, &sprite3));
+        fs::remove_dir_all(folder).unwrap();
+    }
+
+    #[test]
+    fn frames_are_grouped_by_hash_and_pixels() {
+        let folder = test_folder("group");
+        let clear = Rgba([0, 0, 0, 0]);
+        let red = Rgba([255, 0, 0, 255]);
+        let blue = Rgba([0, 0, 255, 255]);
+        let sprite1 = save_frame(&folder, "a", 0, red, clear);
+        let sprite2 = save_frame(&folder, "b", 0, blue, clear);
+        let sprite3 = save_frame(&folder, "c", 0, red, blue);
+
+        // All three are given the same hash, as if they collided, so only the pixels tell them apart
+        let hashed_sprites = [(&sprite1, 1), (&sprite2, 1), (&sprite3, 1)];
+        let groups = group_identical_frames(&folder, &hashed_sprites).unwrap();
+        assert_eq!(groups, vec![vec![&sprite1, &sprite3], vec![&sprite2]]);
+
+        // Frames with different hashes are never compared, so they stay apart
+        let hashed_sprites = [(&sprite1, 1), (&sprite3, 2)];
+        let groups = group_identical_frames(&folder, &hashed_sprites).unwrap();
+        assert_eq!(groups, vec![vec![&sprite1], vec![&sprite3]]);
+        fs::remove_dir_all(folder).unwrap();
+    }
+
+    #[test]
+    fn sprite_is_changed_when_a_duplicate_differs() {
+        let folder = test_folder("changed");
+        let clear = Rgba([0, 0, 0, 0]);
+        let red = Rgba([255, 0, 0, 255]);
+        let sprites = [
+            save_frame(&folder, "a", 0, red, clear),
+            save_frame(&folder, "b", 0, red, clear),
+            save_frame(&folder, "c", 1, red, clear),
+            save_frame(&folder, "d", 1, Rgba([0, 0, 255, 255]), clear),
+        ];
+
+        let hashed_sprites = sprites
+            .iter()
+            .map(|sprite| (sprite, trim_hash(&folder, sprite)))
+            .collect();
+        let changed_sprites = group_changed_sprites(&folder, hashed_sprites).unwrap();
+        assert_eq!(changed_sprites, sprites[2..].to_vec());
+        fs::remove_dir_all(folder).unwrap();
+    }
+}
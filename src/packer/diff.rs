@@ -0,0 +1,138 @@
+use std::path::Path;
+
+use image::{DynamicImage, GenericImageView, Rgba, RgbaImage};
+
+use crate::{
+    error::{Result, SpritePackerError},
+    packer::{layout::Rect, pack::atlas_position},
+    tk2d::{
+        cln::Collection,
+        sprite::{Sprite, SpriteImage},
+    },
+};
+
+/// The pixels that differ between the trimmed frames of two duplicate sprites.
+#[derive(Clone, Debug, Default)]
+pub struct SpriteDiff {
+    /// The number of pixels that differ
+    pub different_pixels: usize,
+    /// The smallest rect containing every differing pixel, from the top left of the trimmed frames
+    pub bounds: Option<Rect>,
+    /// The first trimmed frame faded out, with the differing pixels highlighted in red
+    pub image: RgbaImage,
+}
+
+/// Compare the trimmed frames of two duplicate sprites pixel by pixel.
+/// # Arguments
+/// * `sprites_path` - The path to the sprites folder
+/// * `sprite1` - The first sprite
+/// * `sprite2` - The second sprite
+/// # Returns
+/// * `Result<SpriteDiff>` The pixels that differ
+pub fn diff_sprites(sprites_path: &Path, sprite1: &Sprite, sprite2: &Sprite) -> Result<SpriteDiff> {
+    let sprite_image1 = SpriteImage::open(sprites_path, sprite1)?;
+    let sprite_image2 = SpriteImage::open(sprites_path, sprite2)?;
+    diff(&sprite_image1, &sprite_image2)
+}
+
+/// Compare the trimmed frame of a sprite with the region of the atlas it was dumped from.
+/// # Arguments
+/// * `sprites_path` - The path to the sprites folder
+/// * `collection` - The collection the sprite belongs to
+/// * `sprite` - The sprite
+/// # Returns
+/// * `Result<(SpriteDiff, SpriteImage)>` The pixels that differ, and the frame as it is in the atlas
+pub fn diff_with_atlas(
+    sprites_path: &Path,
+    collection: &Collection,
+    sprite: &Sprite,
+) -> Result<(SpriteDiff, SpriteImage)> {
+    let atlas = image::open(&collection.path).map_err(|source| SpritePackerError::OpenImage {
+        path: collection.path.clone(),
+        source,
+    })?;
+    let sprite_image = SpriteImage::open(sprites_path, sprite)?;
+    let original_image = original_frame(&sprite_image, &atlas);
+    Ok((diff(&sprite_image, &original_image)?, original_image))
+}
+
+/// Cut the region a sprite is packed into out of an atlas, as a frame the same size as the sprite's frame image.
+/// Pixels outside the sprite's trim rect, or outside the atlas, are left transparent.
+/// # Arguments
+/// * `sprite_image` - The sprite and its frame image
+/// * `atlas` - The atlas the sprite was dumped from
+/// # Returns
+/// * `SpriteImage` The sprite and its frame as it is in the atlas
+pub fn original_frame(sprite_image: &SpriteImage, atlas: &DynamicImage) -> SpriteImage {
+    let sprite = &sprite_image.sprite;
+    let frame_height = sprite_image.image.height() as i32;
+    let atlas_width = atlas.width() as i32;
+    let atlas_height = atlas.height() as i32;
+    let mut image = RgbaImage::new(sprite_image.image.width(), sprite_image.image.height());
+    for i in sprite.xr..(sprite.xr + sprite.width) {
+        for j in sprite.yr..(sprite.yr + sprite.height) {
+            let (x, y) = atlas_position(sprite, i, j, atlas_height);
+            if x >= 0 && x < atlas_width && y >= 0 && y < atlas_height {
+                image.put_pixel(
+                    i as u32,
+                    (frame_height - j - 1) as u32,
+                    atlas.get_pixel(x as u32, y as u32),
+                );
+            }
+        }
+    }
+
+    SpriteImage {
+        sprite: sprite.clone(),
+        image: DynamicImage::ImageRgba8(image),
+    }
+}
+
+/// Compare the trimmed frames of two duplicate sprites pixel by pixel.
+/// # Arguments
+/// * `sprite_image1` - The first sprite and its frame image
+/// * `sprite_image2` - The second sprite and its frame image
+/// # Returns
+/// * `Result<SpriteDiff>` The pixels that differ
+pub fn diff(sprite_image1: &SpriteImage, sprite_image2: &SpriteImage) -> Result<SpriteDiff> {
+    let frame1 = sprite_image1.trim();
+    let frame2 = sprite_image2.trim();
+    if frame1.width() != frame2.width() || frame1.height() != frame2.height() {
+        return Err(SpritePackerError::SizeMismatch(
+            sprite_image2.sprite.path.clone(),
+            sprite_image1.sprite.path.clone(),
+        ));
+    }
+
+    let mut different_pixels = 0;
+    let mut bounds: Option<(u32, u32, u32, u32)> = None;
+    let mut image = RgbaImage::new(frame1.width(), frame1.height());
+    for (x, y, pixel1) in frame1.pixels() {
+        let pixel2 = frame2.get_pixel(x, y);
+        if pixel1 == pixel2 {
+            let [r, g, b, a] = pixel1.0;
+            image.put_pixel(x, y, Rgba([r, g, b, a / 4]));
+            continue;
+        }
+
+        different_pixels += 1;
+        bounds = Some(match bounds {
+            Some((x_min, y_min, x_max, y_max)) => {
+                (x_min.min(x), y_min.min(y), x_max.max(x), y_max.max(y))
+            }
+            None => (x, y, x, y),
+        });
+        image.put_pixel(x, y, Rgba([255, 0, 0, 255]));
+    }
+
+    Ok(SpriteDiff {
+        different_pixels,
+        bounds: bounds.map(|(x_min, y_min, x_max, y_max)| Rect {
+            x: x_min,
+            y: y_min,
+            width: x_max - x_min + 1,
+            height: y_max - y_min + 1,
+        }),
+        image,
+    })
+}
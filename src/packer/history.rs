@@ -0,0 +1,241 @@
+use std::{fs, path::PathBuf};
+
+use crate::error::{Result, SpritePackerError};
+
+/// The contents of a file before and after it was overwritten.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FileChange {
+    /// The path to the file
+    pub path: PathBuf,
+    /// The bytes of the file before it was overwritten
+    pub before: Vec<u8>,
+    /// The bytes of the file after it was overwritten
+    pub after: Vec<u8>,
+}
+
+impl FileChange {
+    /// Write the contents of the file from before it was overwritten.
+    /// # Returns
+    /// * `Result<()>` Whether the file was written, or an error if it no longer holds what was written
+    pub fn revert(&self) -> Result<()> {
+        self.expect(&self.after)?;
+        self.write(&self.before)
+    }
+
+    /// Write the contents of the file from after it was overwritten.
+    /// # Returns
+    /// * `Result<()>` Whether the file was written, or an error if it no longer holds what was there before
+    pub fn apply(&self) -> Result<()> {
+        self.expect(&self.before)?;
+        self.write(&self.after)
+    }
+
+    /// Make sure the file still holds the given bytes, so that edits made since are never overwritten.
+    /// # Arguments
+    /// * `bytes` - The bytes the file should hold
+    /// # Returns
+    /// * `Result<()>` An error if the file holds something else or could not be read
+    fn expect(&self, bytes: &[u8]) -> Result<()> {
+        let current = fs::read(&self.path).map_err(|source| SpritePackerError::Io {
+            path: self.path.clone(),
+            source,
+        })?;
+        if current != bytes {
+            return Err(SpritePackerError::FileChanged(self.path.clone()));
+        }
+        Ok(())
+    }
+
+    /// Write bytes to the file.
+    /// # Arguments
+    /// * `bytes` - The bytes to write
+    /// # Returns
+    /// * `Result<()>` Whether the file was written
+    fn write(&self, bytes: &[u8]) -> Result<()> {
+        fs::write(&self.path, bytes).map_err(|source| SpritePackerError::Io {
+            path: self.path.clone(),
+            source,
+        })
+    }
+}
+
+/// An operation that overwrote one or more files, such as replacing the duplicates of a sprite.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Operation {
+    /// A short description of the operation, e.g. `Replace Idle-0-12.png`
+    pub name: String,
+    /// The files that were overwritten, in the order they were overwritten
+    pub changes: Vec<FileChange>,
+}
+
+impl Operation {
+    /// Get the files that the operation overwrote.
+    /// # Returns
+    /// * `Vec<PathBuf>` The path of each file, in the order they were overwritten
+    pub fn paths(&self) -> Vec<PathBuf> {
+        self.changes
+            .iter()
+            .map(|change| change.path.clone())
+            .collect()
+    }
+
+    /// Write every file back to how it was before the operation.
+    /// Nothing is written if any of the files was changed since the operation.
+    /// # Returns
+    /// * `Result<()>` Whether every file was written
+    pub fn revert(&self) -> Result<()> {
+        for change in &self.changes {
+            change.expect(&change.after)?;
+        }
+        for change in self.changes.iter().rev() {
+            change.revert()?;
+        }
+        Ok(())
+    }
+
+    /// Write every file back to how it was after the operation.
+    /// Nothing is written if any of the files was changed since the operation was undone.
+    /// # Returns
+    /// * `Result<()>` Whether every file was written
+    pub fn apply(&self) -> Result<()> {
+        for change in &self.changes {
+            change.expect(&change.before)?;
+        }
+        for change in &self.changes {
+            change.apply()?;
+        }
+        Ok(())
+    }
+}
+
+/// The operations that can be undone and redone.
+#[derive(Clone, Debug, Default)]
+pub struct History {
+    /// The operations that can be undone, oldest first
+    pub undo_stack: Vec<Operation>,
+    /// The operations that can be redone, most recently undone last
+    pub redo_stack: Vec<Operation>,
+}
+
+impl History {
+    /// Record an operation that was just performed.
+    /// This discards every operation that could be redone.
+    /// # Arguments
+    /// * `operation` - The operation
+    pub fn push(&mut self, operation: Operation) {
+        if operation.changes.is_empty() {
+            return;
+        }
+        self.undo_stack.push(operation);
+        self.redo_stack.clear();
+    }
+
+    /// Undo the most recent operation.
+    /// If the files could not all be written, or one was changed since, the operation stays in the undo stack.
+    /// # Returns
+    /// * `Result<Option<&Operation>>` The operation that was undone, or `None` if there was nothing to undo
+    pub fn undo(&mut self) -> Result<Option<&Operation>> {
+        let Some(operation) = self.undo_stack.pop() else {
+            return Ok(None);
+        };
+        if let Err(e) = operation.revert() {
+            self.undo_stack.push(operation);
+            return Err(e);
+        }
+        self.redo_stack.push(operation);
+        Ok(self.redo_stack.last())
+    }
+
+    /// Redo the most recently undone operation.
+    /// If the files could not all be written, or one was changed since, the operation stays in the redo stack.
+    /// # Returns
+    /// * `Result<Option<&Operation>>` The operation that was redone, or `None` if there was nothing to redo
+    pub fn redo(&mut self) -> Result<Option<&Operation>> {
+        let Some(operation) = self.redo_stack.pop() else {
+            return Ok(None);
+        };
+        if let Err(e) = operation.apply() {
+            self.redo_stack.push(operation);
+            return Err(e);
+        }
+        self.undo_stack.push(operation);
+        Ok(self.undo_stack.last())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Write a file to an empty folder in the temporary directory and record it as overwritten from `before` to its contents.
+    fn test_operation(name: &str, before: &str, after: &str) -> Operation {
+        let folder = std::env::temp_dir().join(format!(
+            "spritepacker-history-{name}-{}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&folder);
+        fs::create_dir_all(&folder).unwrap();
+        let path = folder.join("frame.png");
+        fs::write(&path, after).unwrap();
+        Operation {
+            name: format!("Replace {name}"),
+            changes: vec![FileChange {
+                path,
+                before: before.as_bytes().to_vec(),
+                after: after.as_bytes().to_vec(),
+            }],
+        }
+    }
+
+    /// Read the only file an operation changed.
+    fn read(operation: &Operation) -> String {
+        fs::read_to_string(&operation.changes[0].path).unwrap()
+    }
+
+    #[test]
+    fn undo_and_redo_write_files_back() {
+        let operation = test_operation("undo", "before", "after");
+        let mut history = History::default();
+        history.push(operation.clone());
+
+        assert_eq!(history.undo().unwrap(), Some(&operation));
+        assert_eq!(read(&operation), "before");
+        assert!(history.undo().unwrap().is_none());
+
+        assert_eq!(history.redo().unwrap(), Some(&operation));
+        assert_eq!(read(&operation), "after");
+        assert!(history.redo().unwrap().is_none());
+        fs::remove_dir_all(operation.changes[0].path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn file_changed_since_is_not_overwritten() {
+        let operation = test_operation("changed", "before", "after");
+        let mut history = History::default();
+        history.push(operation.clone());
+
+        fs::write(&operation.changes[0].path, "edited").unwrap();
+        assert!(matches!(
+            history.undo(),
+            Err(SpritePackerError::FileChanged(_))
+        ));
+        assert_eq!(read(&operation), "edited");
+        assert_eq!(history.undo_stack.last(), Some(&operation));
+        fs::remove_dir_all(operation.changes[0].path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn push_clears_redo_stack() {
+        let operation = test_operation("push", "before", "after");
+        let mut history = History::default();
+        history.push(operation.clone());
+        history.undo().unwrap();
+        assert_eq!(history.redo_stack.len(), 1);
+
+        history.push(Operation::default());
+        assert_eq!(history.redo_stack.len(), 1);
+        history.push(operation.clone());
+        assert!(history.redo_stack.is_empty());
+        fs::remove_dir_all(operation.changes[0].path.parent().unwrap()).unwrap();
+    }
+}
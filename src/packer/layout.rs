@@ -0,0 +1,201 @@
+/// A rectangle in an atlas, measured in pixels from the top left.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Rect {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Rect {
+    /// Check whether this rectangle lies entirely within another.
+    /// # Arguments
+    /// * `other` - The rectangle that may contain this one
+    /// # Returns
+    /// * `bool` Whether this rectangle is contained by `other`
+    pub fn is_contained_in(&self, other: &Rect) -> bool {
+        self.x >= other.x
+            && self.y >= other.y
+            && self.x + self.width <= other.x + other.width
+            && self.y + self.height <= other.y + other.height
+    }
+
+    /// Check whether this rectangle overlaps another.
+    /// # Arguments
+    /// * `other` - The other rectangle
+    /// # Returns
+    /// * `bool` Whether the rectangles share any pixels
+    pub fn intersects(&self, other: &Rect) -> bool {
+        self.x < other.x + other.width
+            && other.x < self.x + self.width
+            && self.y < other.y + other.height
+            && other.y < self.y + self.height
+    }
+}
+
+/// Lays out rectangles in a bin with the MaxRects algorithm, using the best short side fit heuristic.
+#[derive(Clone, Debug)]
+pub struct MaxRects {
+    free_rects: Vec<Rect>,
+}
+
+impl MaxRects {
+    /// Create an empty bin.
+    /// # Arguments
+    /// * `width` - The width of the bin
+    /// * `height` - The height of the bin
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            free_rects: vec![Rect {
+                x: 0,
+                y: 0,
+                width,
+                height,
+            }],
+        }
+    }
+
+    /// Find a place for a rectangle and mark it as used.
+    /// # Arguments
+    /// * `width` - The width of the rectangle
+    /// * `height` - The height of the rectangle
+    /// # Returns
+    /// * `Option<Rect>` Where the rectangle was placed, or `None` if it does not fit
+    pub fn insert(&mut self, width: u32, height: u32) -> Option<Rect> {
+        let placed = self
+            .free_rects
+            .iter()
+            .filter(|free| free.width >= width && free.height >= height)
+            .min_by_key(|free| {
+                let leftover_x = free.width - width;
+                let leftover_y = free.height - height;
+                (leftover_x.min(leftover_y), leftover_x.max(leftover_y))
+            })
+            .map(|free| Rect {
+                x: free.x,
+                y: free.y,
+                width,
+                height,
+            })?;
+
+        let mut free_rects = Vec::with_capacity(self.free_rects.len() + 4);
+        for free in &self.free_rects {
+            if free.intersects(&placed) {
+                Self::split(free, &placed, &mut free_rects);
+            } else {
+                free_rects.push(*free);
+            }
+        }
+        self.free_rects = Self::prune(free_rects);
+
+        Some(placed)
+    }
+
+    /// Split a free rectangle into the maximal rectangles left around a placed one.
+    /// # Arguments
+    /// * `free` - The free rectangle that overlaps `placed`
+    /// * `placed` - The rectangle that was placed
+    /// * `free_rects` - Where to put the remaining free rectangles
+    fn split(free: &Rect, placed: &Rect, free_rects: &mut Vec<Rect>) {
+        if placed.x > free.x {
+            free_rects.push(Rect {
+                width: placed.x - free.x,
+                ..*free
+            });
+        }
+        if placed.x + placed.width < free.x + free.width {
+            free_rects.push(Rect {
+                x: placed.x + placed.width,
+                width: free.x + free.width - (placed.x + placed.width),
+                ..*free
+            });
+        }
+        if placed.y > free.y {
+            free_rects.push(Rect {
+                height: placed.y - free.y,
+                ..*free
+            });
+        }
+        if placed.y + placed.height < free.y + free.height {
+            free_rects.push(Rect {
+                y: placed.y + placed.height,
+                height: free.y + free.height - (placed.y + placed.height),
+                ..*free
+            });
+        }
+    }
+
+    /// Remove free rectangles that are contained by another free rectangle.
+    /// # Arguments
+    /// * `free_rects` - The free rectangles
+    /// # Returns
+    /// * `Vec<Rect>` The free rectangles that are not redundant
+    fn prune(free_rects: Vec<Rect>) -> Vec<Rect> {
+        let mut pruned: Vec<Rect> = Vec::with_capacity(free_rects.len());
+        for (i, rect) in free_rects.iter().enumerate() {
+            let redundant = free_rects.iter().enumerate().any(|(j, other)| {
+                i != j && rect.is_contained_in(other) && (rect != other || j < i)
+            });
+            if !redundant {
+                pruned.push(*rect);
+            }
+        }
+        pruned
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn placed_rects_do_not_overlap_and_stay_in_bin() {
+        let bin = Rect {
+            x: 0,
+            y: 0,
+            width: 64,
+            height: 64,
+        };
+        let mut max_rects = MaxRects::new(bin.width, bin.height);
+        let sizes = [
+            (32, 16),
+            (16, 32),
+            (8, 8),
+            (24, 24),
+            (16, 8),
+            (8, 16),
+            (20, 4),
+        ];
+        let placed: Vec<Rect> = sizes
+            .iter()
+            .map(|&(width, height)| max_rects.insert(width, height).unwrap())
+            .collect();
+
+        for (i, rect) in placed.iter().enumerate() {
+            assert_eq!((rect.width, rect.height), sizes[i]);
+            assert!(rect.is_contained_in(&bin));
+            for other in &placed[i + 1..] {
+                assert!(!rect.intersects(other), "{rect:?} overlaps {other:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn rect_that_does_not_fit_is_not_placed() {
+        let mut max_rects = MaxRects::new(32, 32);
+        assert!(max_rects.insert(33, 1).is_none());
+        assert!(max_rects.insert(1, 33).is_none());
+
+        assert!(max_rects.insert(32, 32).is_some());
+        assert!(max_rects.insert(1, 1).is_none());
+    }
+
+    #[test]
+    fn filled_bin_is_packed_exactly() {
+        let mut max_rects = MaxRects::new(16, 16);
+        for _ in 0..4 {
+            assert!(max_rects.insert(8, 8).is_some());
+        }
+        assert!(max_rects.insert(1, 1).is_none());
+    }
+}
@@ -0,0 +1,235 @@
+use std::{fs, path::Path};
+
+use rayon::iter::{IndexedParallelIterator, IntoParallelRefIterator};
+
+use crate::{
+    error::{Result, SpritePackerError},
+    packer::cancel::{check_cancelled, CancelToken},
+    tk2d::{
+        anim::Animation,
+        clip::Clip,
+        cln::Collection,
+        info::{AnimInfo, SpriteInfo},
+    },
+};
+
+/// The collections and animations loaded from a dump.
+#[derive(Debug, Default)]
+pub struct Dump {
+    pub collections: Vec<Collection>,
+    pub animations: Vec<Animation>,
+    /// Problems with animations or frames that were skipped while loading
+    pub errors: Vec<SpritePackerError>,
+}
+
+impl Dump {
+    /// Add the collections, animations and errors of another dump to this one.
+    /// Sprites of collections that are already loaded are added to them, so that a collection spread over several animations is only listed once.
+    /// # Arguments
+    /// * `other` - The dump to add, e.g. a single animation streamed by `load_incrementally`
+    pub fn extend(&mut self, other: Dump) {
+        for collection in other.collections {
+            if let Some(loaded_collection) = self
+                .collections
+                .iter_mut()
+                .find(|cln| cln.name == collection.name)
+            {
+                loaded_collection.sprites.extend(collection.sprites);
+            } else {
+                self.collections.push(collection);
+            }
+        }
+        self.animations.extend(other.animations);
+        self.errors.extend(other.errors);
+    }
+}
+
+/// Load collections and animations from sprite files on disk.
+/// # Arguments
+/// * `sprites_path` - The path to the sprites folder
+/// # Returns
+/// * `Result<Dump>` The loaded dump, or an error if the sprites folder could not be read
+pub fn load_collections_and_animations(sprites_path: &Path) -> Result<Dump> {
+    let mut dump = Dump::default();
+    load_incrementally(sprites_path, None, |animation_dump, _| {
+        dump.extend(animation_dump)
+    })?;
+
+    Ok(dump)
+}
+
+/// Load collections and animations from sprite files on disk one animation folder at a time, so that they can be shown before the whole dump has been read.
+/// # Arguments
+/// * `sprites_path` - The path to the sprites folder
+/// * `cancel` - A token to stop loading early, if any
+/// * `on_animation` - Called with each animation folder as it is loaded, holding the animation, the sprites it adds to each collection and any problems with it, along with the fraction of folders loaded so far
+/// # Returns
+/// * `Result<()>` Whether the sprites folder could be read
+pub fn load_incrementally(
+    sprites_path: &Path,
+    cancel: Option<&CancelToken>,
+    mut on_animation: impl FnMut(Dump, f32),
+) -> Result<()> {
+    let anim_paths = fs::read_dir(sprites_path)
+        .map_err(|source| SpritePackerError::Io {
+            path: sprites_path.to_path_buf(),
+            source,
+        })?
+        .flatten()
+        .map(|anim_entry| anim_entry.path())
+        .filter(|anim_path| anim_path.is_dir())
+        .collect::<Vec<_>>();
+
+    for (index, anim_path) in anim_paths.iter().enumerate() {
+        check_cancelled(cancel)?;
+        let mut animation_dump = Dump::default();
+        match load_animation(
+            sprites_path,
+            anim_path,
+            &mut animation_dump.collections,
+            &mut animation_dump.errors,
+        ) {
+            Ok(Some(animation)) => animation_dump.animations.push(animation),
+            Ok(None) => {}
+            Err(e) => animation_dump.errors.push(e),
+        }
+        on_animation(animation_dump, (index + 1) as f32 / anim_paths.len() as f32);
+    }
+
+    Ok(())
+}
+
+/// Load a single animation folder, adding its sprites to the collections they belong to.
+/// # Arguments
+/// * `sprites_path` - The path to the sprites folder
+/// * `anim_path` - The path to the animation folder
+/// * `collections` - The collections to add the animation's sprites to
+/// * `errors` - Where to put problems with frames that are skipped
+/// # Returns
+/// * `Result<Option<Animation>>` The loaded animation, or `None` if the folder has no SpriteInfo.json
+pub fn load_animation(
+    sprites_path: &Path,
+    anim_path: &Path,
+    collections: &mut Vec<Collection>,
+    errors: &mut Vec<SpritePackerError>,
+) -> Result<Option<Animation>> {
+    let sprite_info_path = anim_path.join("0.Atlases").join("SpriteInfo.json");
+    let Ok(sprite_info_text) = fs::read_to_string(&sprite_info_path) else {
+        return Ok(None);
+    };
+    let sprite_info: SpriteInfo =
+        serde_json::from_str(&sprite_info_text).map_err(|source| SpritePackerError::Json {
+            path: sprite_info_path.clone(),
+            source,
+        })?;
+
+    let mut sprites = vec![];
+    for i in 0..sprite_info.id.len() {
+        let sprite = sprite_info
+            .at(i)
+            .ok_or_else(|| SpritePackerError::MalformedSpriteInfo {
+                path: sprite_info_path.clone(),
+                index: i,
+            })?;
+        if !Path::new(&sprite.path).exists() && !sprites_path.join(&sprite.path).exists() {
+            continue;
+        }
+        sprites.push(sprite);
+    }
+
+    let mut clips = vec![];
+    if let Ok(clip_paths) = fs::read_dir(anim_path) {
+        for clip_entry in clip_paths.flatten() {
+            if !clip_entry.path().is_dir() || clip_entry.file_name() == "0.Atlases" {
+                continue;
+            }
+            if let Some(clip) = load_clip(&clip_entry.path(), &sprite_info, errors) {
+                clips.push(clip);
+            }
+        }
+    }
+
+    let Some(anim_name) = anim_path.file_name().and_then(|name| name.to_str()) else {
+        return Ok(None);
+    };
+
+    for sprite in sprites {
+        if let Some(collection) = collections
+            .iter_mut()
+            .find(|cln| cln.name == sprite.collection_name)
+        {
+            collection.sprites.push(sprite);
+        } else {
+            let collection_name = sprite.collection_name.clone();
+            collections.push(Collection {
+                name: collection_name.clone(),
+                path: anim_path
+                    .join("0.Atlases")
+                    .join(format!("{}.png", collection_name)),
+                sprites: vec![sprite],
+            });
+        }
+    }
+
+    Ok(Some(Animation {
+        name: anim_name.to_string(),
+        clips,
+    }))
+}
+
+/// Load a single clip folder.
+/// # Arguments
+/// * `clip_path` - The path to the clip folder
+/// * `sprite_info` - The sprite info of the animation the clip belongs to
+/// * `errors` - Where to put problems with frames that are skipped
+/// # Returns
+/// * `Option<Clip>` The loaded clip
+fn load_clip(
+    clip_path: &Path,
+    sprite_info: &SpriteInfo,
+    errors: &mut Vec<SpritePackerError>,
+) -> Option<Clip> {
+    let mut frames = vec![];
+    let mut fps = 12.;
+    let mut loop_start = 0;
+    if let Ok(frame_paths) = fs::read_dir(clip_path) {
+        for frame_entry in frame_paths.flatten() {
+            if frame_entry.file_name() == "AnimInfo.json" {
+                if let Ok(anim_info_text) = fs::read_to_string(frame_entry.path()) {
+                    let anim_info: AnimInfo =
+                        serde_json::from_str(&anim_info_text).unwrap_or(AnimInfo {
+                            fps: 12.0,
+                            loop_start: 0,
+                            num_frames: 0,
+                            collection_name: "".to_string(),
+                        });
+                    fps = anim_info.fps;
+                    loop_start = anim_info.loop_start;
+                }
+                continue;
+            } else if frame_entry
+                .path()
+                .extension()
+                .map_or(true, |ext| ext != "png")
+            {
+                continue;
+            }
+
+            let sprite = sprite_info
+                .path
+                .par_iter()
+                .position_first(|path| {
+                    let stripped_path = path.replace("./", "/").replace(".\\", "\\");
+                    frame_entry.path().ends_with(stripped_path)
+                })
+                .and_then(|index| sprite_info.at(index));
+            match sprite {
+                Some(sprite) => frames.push(sprite),
+                None => errors.push(SpritePackerError::UnknownFrame(frame_entry.path())),
+            }
+        }
+    }
+
+    let clip_name = clip_path.file_name()?.to_str()?;
+    Some(Clip::new(clip_name.to_string(), frames, fps, loop_start))
+}
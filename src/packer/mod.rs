@@ -0,0 +1,82 @@
+pub mod backup;
+pub mod cache;
+pub mod cancel;
+pub mod check;
+pub mod diff;
+pub mod history;
+pub mod layout;
+pub mod load;
+pub mod pack;
+pub mod project;
+pub mod replace;
+pub mod resolve;
+pub mod trim;
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use image::{DynamicImage, ImageError, ImageFormat};
+
+use crate::error::{Result, SpritePackerError};
+
+/// Resolve the path of a file in a dump, which may be either relative to the sprites path or absolute.
+/// # Arguments
+/// * `sprites_path` - The path to the sprites folder
+/// * `path` - The path of the file as stored in the dump
+/// # Returns
+/// * `PathBuf` The resolved path
+pub fn resolve_path(sprites_path: &Path, path: &str) -> PathBuf {
+    let joined_path = sprites_path.join(path);
+    if joined_path.exists() {
+        joined_path
+    } else {
+        PathBuf::from(path)
+    }
+}
+
+/// Save an image, creating the folder it is saved to if needed.
+/// The image is written to a temporary file next to it first and then moved into place, so an existing file is never left half-written.
+/// # Arguments
+/// * `image` - The image to save
+/// * `path` - The path to save the image to, whose extension decides the format
+/// # Returns
+/// * `Result<()>` Whether the image was saved
+pub fn save_image(image: &DynamicImage, path: &Path) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|source| SpritePackerError::SaveImage {
+            path: path.to_path_buf(),
+            source: ImageError::IoError(source),
+        })?;
+    }
+    let format = ImageFormat::from_path(path).map_err(|source| SpritePackerError::SaveImage {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let temp_path = temp_path(path);
+    image
+        .save_with_format(&temp_path, format)
+        .map_err(|source| SpritePackerError::SaveImage {
+            path: path.to_path_buf(),
+            source,
+        })?;
+    fs::rename(&temp_path, path).map_err(|source| {
+        let _ = fs::remove_file(&temp_path);
+        SpritePackerError::SaveImage {
+            path: path.to_path_buf(),
+            source: ImageError::IoError(source),
+        }
+    })
+}
+
+/// Get the temporary file that a file is written to before it is moved into place.
+/// # Arguments
+/// * `path` - The path to the file
+/// # Returns
+/// * `PathBuf` The path with `.part` appended
+pub fn temp_path(path: &Path) -> PathBuf {
+    let mut temp_path = path.as_os_str().to_os_string();
+    temp_path.push(".part");
+    PathBuf::from(temp_path)
+}
@@ -0,0 +1,674 @@
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+};
+
+use image::{DynamicImage, RgbaImage};
+use rayon::iter::{
+    IndexedParallelIterator, IntoParallelIterator, IntoParallelRefIterator, ParallelIterator,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{Result, SpritePackerError},
+    packer::{
+        backup::Backup,
+        cancel::{check_cancelled, CancelToken},
+        layout::{MaxRects, Rect},
+        save_image, temp_path,
+        trim::retrim_collection,
+    },
+    tk2d::{
+        cln::Collection,
+        info::SpriteInfo,
+        sprite::{Sprite, SpriteImage},
+    },
+};
+
+/// The name of the folder next to each original atlas that generated atlases are saved to by default.
+pub const PACKED_DIR_NAME: &str = "Packed";
+
+/// How the sprites of a collection are laid out in a generated atlas.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub enum Layout {
+    /// Paint sprites over the collection's atlas at their original positions
+    #[default]
+    Original,
+    /// Lay sprites out into a new atlas no larger than `max_size` on either side,
+    /// optionally fitting their trim rects to the opaque pixels of their frames first
+    Repack {
+        max_size: u32,
+        padding: u32,
+        retrim: bool,
+    },
+}
+
+/// Pack a collection of sprites into an atlas.
+/// # Arguments
+/// * `collection` - The collection to pack
+/// * `sprites_path` - The path to the sprites folder
+/// * `cancel` - The token to stop packing early with, if any
+/// * `on_progress` - Called with the fraction of sprites packed so far
+/// # Returns
+/// * `Result<DynamicImage>` The generated atlas
+pub fn pack_collection(
+    collection: &Collection,
+    sprites_path: &Path,
+    cancel: Option<&CancelToken>,
+    on_progress: impl Fn(f32) + Sync,
+) -> Result<DynamicImage> {
+    let atlas = image::open(&collection.path).map_err(|source| SpritePackerError::OpenImage {
+        path: collection.path.clone(),
+        source,
+    })?;
+    let sprites = collection.sprites.iter().collect::<Vec<_>>();
+    paint_sprites(atlas, &sprites, sprites_path, cancel, on_progress)
+}
+
+/// Lay the sprites of a collection out into a brand-new atlas.
+/// Duplicates of a sprite share a single place in the atlas, and no sprite is flipped.
+/// # Arguments
+/// * `collection` - The collection to pack
+/// * `sprites_path` - The path to the sprites folder
+/// * `max_size` - The largest width and height the atlas may have
+/// * `padding` - The number of empty pixels to leave between sprites
+/// * `cancel` - The token to stop packing early with, if any
+/// * `on_progress` - Called with the fraction of sprites packed so far
+/// # Returns
+/// * `Result<(DynamicImage, Vec<Sprite>)>` The generated atlas and the sprites at their new positions
+pub fn repack_collection(
+    collection: &Collection,
+    sprites_path: &Path,
+    max_size: u32,
+    padding: u32,
+    cancel: Option<&CancelToken>,
+    on_progress: impl Fn(f32) + Sync,
+) -> Result<(DynamicImage, Vec<Sprite>)> {
+    let mut unique_sprites = collection
+        .sprites
+        .iter()
+        .fold(
+            HashMap::<u32, &Sprite>::new(),
+            |mut unique_sprites, sprite| {
+                unique_sprites.entry(sprite.id).or_insert(sprite);
+                unique_sprites
+            },
+        )
+        .into_values()
+        .collect::<Vec<_>>();
+    unique_sprites.sort_by_key(|sprite| {
+        (
+            std::cmp::Reverse(sprite.width.max(sprite.height)),
+            std::cmp::Reverse(sprite.width.min(sprite.height)),
+            sprite.id,
+        )
+    });
+
+    let mut bin = MaxRects::new(max_size + padding, max_size + padding);
+    let mut positions = HashMap::<u32, (u32, u32)>::new();
+    for sprite in unique_sprites {
+        let width = sprite.width.max(0) as u32;
+        let height = sprite.height.max(0) as u32;
+        if width == 0 || height == 0 {
+            positions.insert(sprite.id, (0, 0));
+            continue;
+        }
+        let rect = bin
+            .insert(width + padding, height + padding)
+            .ok_or_else(|| SpritePackerError::AtlasTooSmall(collection.name.clone(), max_size))?;
+        positions.insert(sprite.id, (rect.x, rect.y));
+    }
+
+    let used_width = collection
+        .sprites
+        .iter()
+        .map(|sprite| positions[&sprite.id].0 + sprite.width.max(0) as u32)
+        .max()
+        .unwrap_or(0);
+    let used_height = collection
+        .sprites
+        .iter()
+        .map(|sprite| positions[&sprite.id].1 + sprite.height.max(0) as u32)
+        .max()
+        .unwrap_or(0);
+    let atlas_width = used_width.max(1).next_power_of_two().min(max_size);
+    let atlas_height = used_height.max(1).next_power_of_two().min(max_size);
+
+    let sprites = collection
+        .sprites
+        .iter()
+        .map(|sprite| {
+            let (x, y) = positions[&sprite.id];
+            Sprite {
+                x: x as i32,
+                y: atlas_height as i32 - y as i32 - sprite.height,
+                flipped: false,
+                ..sprite.clone()
+            }
+        })
+        .collect::<Vec<_>>();
+
+    // Duplicates share a place in the atlas, so only the first of them is painted into it
+    let mut painted_ids = HashSet::new();
+    let painted_sprites = sprites
+        .iter()
+        .filter(|sprite| painted_ids.insert(sprite.id))
+        .collect::<Vec<_>>();
+    let gen_atlas = paint_sprites(
+        DynamicImage::new_rgba8(atlas_width, atlas_height),
+        &painted_sprites,
+        sprites_path,
+        cancel,
+        on_progress,
+    )?;
+
+    Ok((gen_atlas, sprites))
+}
+
+/// Paint the trimmed frames of sprites into an atlas.
+/// The frames are decoded and laid out into patches of the atlas in parallel, and the patches are then copied into the atlas row by row in the order of the sprites, so no pixel is written under a lock and overlapping sprites are painted in a stable order.
+/// # Arguments
+/// * `atlas` - The atlas to paint into
+/// * `sprites` - The sprites to paint
+/// * `sprites_path` - The path to the sprites folder
+/// * `cancel` - The token to stop painting early with, if any
+/// * `on_progress` - Called with the fraction of sprites decoded so far
+/// # Returns
+/// * `Result<DynamicImage>` The painted atlas
+fn paint_sprites(
+    atlas: DynamicImage,
+    sprites: &[&Sprite],
+    sprites_path: &Path,
+    cancel: Option<&CancelToken>,
+    on_progress: impl Fn(f32) + Sync,
+) -> Result<DynamicImage> {
+    let mut atlas = atlas.into_rgba8();
+    let (atlas_width, atlas_height) = atlas.dimensions();
+    let sprite_num = AtomicUsize::new(0);
+    let patches = sprites
+        .par_iter()
+        .map(|sprite| -> Result<Option<(Rect, RgbaImage)>> {
+            check_cancelled(cancel)?;
+            let frame_image = SpriteImage::open(sprites_path, sprite)?;
+            let patch = atlas_patch(sprite, &frame_image.image, atlas_width, atlas_height);
+
+            let num = sprite_num.fetch_add(1, Ordering::Relaxed) + 1;
+            on_progress(num as f32 / sprites.len() as f32);
+            Ok(patch)
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    for (rect, patch) in patches.iter().flatten() {
+        copy_patch(&mut atlas, rect, patch);
+    }
+
+    Ok(DynamicImage::ImageRgba8(atlas))
+}
+
+/// Lay the trimmed region of a frame out the way it is packed into an atlas.
+/// # Arguments
+/// * `sprite` - The sprite the frame belongs to
+/// * `frame_image` - The frame image as dumped by GODump
+/// * `atlas_width` - The width of the atlas
+/// * `atlas_height` - The height of the atlas
+/// # Returns
+/// * `Option<(Rect, RgbaImage)>` The region of the atlas the sprite is packed into and its pixels, or `None` if it lies outside the atlas
+fn atlas_patch(
+    sprite: &Sprite,
+    frame_image: &DynamicImage,
+    atlas_width: u32,
+    atlas_height: u32,
+) -> Option<(Rect, RgbaImage)> {
+    let rect = atlas_rect(sprite, atlas_width, atlas_height)?;
+    let frame_pixels = match frame_image.as_rgba8() {
+        Some(frame_pixels) => Cow::Borrowed(frame_pixels),
+        None => Cow::Owned(frame_image.to_rgba8()),
+    };
+    let frame_height = frame_image.height() as i32;
+    let mut patch = RgbaImage::new(rect.width, rect.height);
+    for i in sprite.xr..(sprite.xr + sprite.width) {
+        for j in sprite.yr..(sprite.yr + sprite.height) {
+            let (x, y) = atlas_position(sprite, i, j, atlas_height as i32);
+            let x = x - rect.x as i32;
+            let y = y - rect.y as i32;
+            if x >= 0 && x < rect.width as i32 && y >= 0 && y < rect.height as i32 {
+                patch.put_pixel(
+                    x as u32,
+                    y as u32,
+                    *frame_pixels.get_pixel(i as u32, (frame_height - j - 1) as u32),
+                );
+            }
+        }
+    }
+
+    Some((rect, patch))
+}
+
+/// Copy a patch into a region of an atlas one row at a time.
+/// # Arguments
+/// * `atlas` - The atlas to copy into
+/// * `rect` - The region of the atlas, which must lie within it and be the size of the patch
+/// * `patch` - The pixels of the region
+fn copy_patch(atlas: &mut RgbaImage, rect: &Rect, patch: &RgbaImage) {
+    let atlas_row_len = atlas.width() as usize * 4;
+    let patch_row_len = rect.width as usize * 4;
+    let atlas_pixels: &mut [u8] = atlas;
+    for (row, patch_row) in patch.chunks_exact(patch_row_len).enumerate() {
+        let start = (rect.y as usize + row) * atlas_row_len + rect.x as usize * 4;
+        atlas_pixels[start..start + patch_row_len].copy_from_slice(patch_row);
+    }
+}
+
+/// Pack a collection into an atlas and save it, along with its sprite info if it was repacked.
+/// # Arguments
+/// * `collection` - The collection to pack
+/// * `sprites_path` - The path to the sprites folder
+/// * `atlas_path` - The path to save the atlas to
+/// * `layout` - How to lay the sprites out in the atlas
+/// * `backup` - The backup to copy existing files into before they are overwritten, if any
+/// * `cancel` - The token to stop packing early with, if any
+/// * `on_progress` - Called with the fraction of sprites packed so far
+/// # Returns
+/// * `Result<()>` Whether the collection was packed and saved
+pub fn pack_and_save(
+    collection: &Collection,
+    sprites_path: &Path,
+    atlas_path: &Path,
+    layout: Layout,
+    backup: Option<&Backup>,
+    cancel: Option<&CancelToken>,
+    on_progress: impl Fn(f32) + Sync,
+) -> Result<()> {
+    check_atlas_path(collection, atlas_path, layout)?;
+    let (atlas, repacked_sprites) =
+        build_atlas(collection, sprites_path, layout, cancel, on_progress)?;
+    // Once saving has started, the atlas and its sprite info are always saved together
+    check_cancelled(cancel)?;
+    save_atlas(&atlas, atlas_path, backup)?;
+    if let Some(sprites) = repacked_sprites {
+        save_sprite_info(&sprites, &sprite_info_path(atlas_path), backup)?;
+    }
+    Ok(())
+}
+
+/// A region of an atlas that a sprite would be packed into.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AtlasRegion {
+    /// The name of the sprite, or of the first of its duplicates
+    pub sprite_name: String,
+    /// Where the sprite would be packed, from the top left of the atlas
+    pub rect: Rect,
+    /// The number of pixels in the region that would change
+    pub changed_pixels: usize,
+}
+
+/// What packing a collection would write, without writing anything.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PackReport {
+    /// The path the atlas would be saved to
+    pub atlas_path: PathBuf,
+    /// The path the sprite info would be saved to, if the collection would be repacked
+    pub sprite_info_path: Option<PathBuf>,
+    /// The width of the atlas that would be saved
+    pub atlas_width: u32,
+    /// The height of the atlas that would be saved
+    pub atlas_height: u32,
+    /// The number of pixels that would differ from the atlas already at `atlas_path`,
+    /// or every pixel if there is none or it has a different size
+    pub changed_pixels: usize,
+    /// The regions of the atlas that sprites would be packed into
+    pub regions: Vec<AtlasRegion>,
+}
+
+/// Pack a collection into an atlas in memory and report what saving it would overwrite.
+/// This generates the atlas exactly as `pack_and_save` would.
+/// # Arguments
+/// * `collection` - The collection to pack
+/// * `sprites_path` - The path to the sprites folder
+/// * `atlas_path` - The path the atlas would be saved to
+/// * `layout` - How to lay the sprites out in the atlas
+/// * `cancel` - The token to stop packing early with, if any
+/// * `on_progress` - Called with the fraction of sprites packed so far
+/// # Returns
+/// * `Result<PackReport>` The files that would be written and the pixels that would change
+pub fn dry_run_pack(
+    collection: &Collection,
+    sprites_path: &Path,
+    atlas_path: &Path,
+    layout: Layout,
+    cancel: Option<&CancelToken>,
+    on_progress: impl Fn(f32) + Sync,
+) -> Result<PackReport> {
+    check_atlas_path(collection, atlas_path, layout)?;
+    let (atlas, repacked_sprites) =
+        build_atlas(collection, sprites_path, layout, cancel, on_progress)?;
+    let atlas = atlas.to_rgba8();
+    let existing_atlas = image::open(atlas_path)
+        .ok()
+        .map(|existing_atlas| existing_atlas.to_rgba8())
+        .filter(|existing_atlas| existing_atlas.dimensions() == atlas.dimensions());
+
+    let sprites = repacked_sprites.as_deref().unwrap_or(&collection.sprites);
+    let mut region_ids = HashSet::new();
+    let regions = sprites
+        .iter()
+        .filter(|sprite| region_ids.insert(sprite.id))
+        .filter_map(|sprite| {
+            let rect = atlas_rect(sprite, atlas.width(), atlas.height())?;
+            Some(AtlasRegion {
+                sprite_name: sprite.name.clone(),
+                rect,
+                changed_pixels: count_changed_pixels(&atlas, existing_atlas.as_ref(), &rect),
+            })
+        })
+        .collect();
+    let whole_atlas = Rect {
+        x: 0,
+        y: 0,
+        width: atlas.width(),
+        height: atlas.height(),
+    };
+
+    Ok(PackReport {
+        atlas_path: atlas_path.to_path_buf(),
+        sprite_info_path: repacked_sprites.map(|_| sprite_info_path(atlas_path)),
+        atlas_width: atlas.width(),
+        atlas_height: atlas.height(),
+        changed_pixels: count_changed_pixels(&atlas, existing_atlas.as_ref(), &whole_atlas),
+        regions,
+    })
+}
+
+/// Make sure that a repacked atlas is not saved over the collection's original atlas.
+/// The dump's SpriteInfo.json would still describe the original layout, so loading, packing and
+/// comparing with the original atlas would all read the wrong regions afterwards.
+/// # Arguments
+/// * `collection` - The collection to pack
+/// * `atlas_path` - The path the atlas would be saved to
+/// * `layout` - How the sprites would be laid out in the atlas
+/// # Returns
+/// * `Result<()>` An error if the atlas would be repacked over the original one
+fn check_atlas_path(collection: &Collection, atlas_path: &Path, layout: Layout) -> Result<()> {
+    let is_original_atlas = atlas_path == collection.path
+        || matches!(
+            (fs::canonicalize(atlas_path), fs::canonicalize(&collection.path)),
+            (Ok(atlas_path), Ok(original_path)) if atlas_path == original_path
+        );
+    if is_original_atlas && matches!(layout, Layout::Repack { .. }) {
+        return Err(SpritePackerError::RepackOverOriginal(
+            collection.name.clone(),
+        ));
+    }
+    Ok(())
+}
+
+/// Pack a collection into an atlas in memory.
+/// # Arguments
+/// * `collection` - The collection to pack
+/// * `sprites_path` - The path to the sprites folder
+/// * `layout` - How to lay the sprites out in the atlas
+/// * `cancel` - The token to stop packing early with, if any
+/// * `on_progress` - Called with the fraction of sprites packed so far
+/// # Returns
+/// * `Result<(DynamicImage, Option<Vec<Sprite>>)>` The generated atlas, and the sprites at their new positions if they were repacked
+fn build_atlas(
+    collection: &Collection,
+    sprites_path: &Path,
+    layout: Layout,
+    cancel: Option<&CancelToken>,
+    on_progress: impl Fn(f32) + Sync,
+) -> Result<(DynamicImage, Option<Vec<Sprite>>)> {
+    match layout {
+        Layout::Original => {
+            let atlas = pack_collection(collection, sprites_path, cancel, on_progress)?;
+            Ok((atlas, None))
+        }
+        Layout::Repack {
+            max_size,
+            padding,
+            retrim,
+        } => {
+            let (atlas, sprites) = if retrim {
+                let collection = retrim_collection(sprites_path, collection)?;
+                repack_collection(
+                    &collection,
+                    sprites_path,
+                    max_size,
+                    padding,
+                    cancel,
+                    on_progress,
+                )?
+            } else {
+                repack_collection(
+                    collection,
+                    sprites_path,
+                    max_size,
+                    padding,
+                    cancel,
+                    on_progress,
+                )?
+            };
+            Ok((atlas, Some(sprites)))
+        }
+    }
+}
+
+/// Count the pixels in a region of a generated atlas that differ from an existing atlas.
+/// # Arguments
+/// * `atlas` - The generated atlas
+/// * `existing_atlas` - The atlas it would overwrite, if there is one of the same size
+/// * `rect` - The region to compare
+/// # Returns
+/// * `usize` The number of differing pixels, or every pixel in the region if there is no existing atlas
+fn count_changed_pixels(
+    atlas: &RgbaImage,
+    existing_atlas: Option<&RgbaImage>,
+    rect: &Rect,
+) -> usize {
+    let Some(existing_atlas) = existing_atlas else {
+        return (rect.width * rect.height) as usize;
+    };
+    (rect.y..rect.y + rect.height)
+        .into_par_iter()
+        .map(|y| {
+            (rect.x..rect.x + rect.width)
+                .filter(|&x| atlas.get_pixel(x, y) != existing_atlas.get_pixel(x, y))
+                .count()
+        })
+        .sum()
+}
+
+/// Get the region of an atlas that a sprite is packed into.
+/// # Arguments
+/// * `sprite` - The sprite
+/// * `atlas_width` - The width of the atlas
+/// * `atlas_height` - The height of the atlas
+/// # Returns
+/// * `Option<Rect>` The part of the sprite's region that lies within the atlas, from the top left, or `None` if it is empty
+pub fn atlas_rect(sprite: &Sprite, atlas_width: u32, atlas_height: u32) -> Option<Rect> {
+    let (width, height) = if sprite.flipped {
+        (sprite.height, sprite.width)
+    } else {
+        (sprite.width, sprite.height)
+    };
+    let left = sprite.x;
+    let top = atlas_height as i32 - sprite.y - height;
+    let x_min = left.max(0);
+    let y_min = top.max(0);
+    let x_max = (left + width).min(atlas_width as i32);
+    let y_max = (top + height).min(atlas_height as i32);
+    if x_min >= x_max || y_min >= y_max {
+        return None;
+    }
+    Some(Rect {
+        x: x_min as u32,
+        y: y_min as u32,
+        width: (x_max - x_min) as u32,
+        height: (y_max - y_min) as u32,
+    })
+}
+
+/// Save the positions of sprites in a generated atlas in GODump's SpriteInfo.json format.
+/// # Arguments
+/// * `sprites` - The sprites at their positions in the atlas
+/// * `sprite_info_path` - The path to save the sprite info to
+/// * `backup` - The backup to copy the existing sprite info into before it is overwritten, if any
+/// # Returns
+/// * `Result<()>` Whether the sprite info was saved
+pub fn save_sprite_info(
+    sprites: &[Sprite],
+    sprite_info_path: &Path,
+    backup: Option<&Backup>,
+) -> Result<()> {
+    if let Some(backup) = backup {
+        backup.snapshot(sprite_info_path)?;
+    }
+    let sprite_info = SpriteInfo::from_sprites(sprites);
+    let sprite_info_text =
+        serde_json::to_string_pretty(&sprite_info).map_err(|source| SpritePackerError::Json {
+            path: sprite_info_path.to_path_buf(),
+            source,
+        })?;
+    fs::write(sprite_info_path, sprite_info_text).map_err(|source| SpritePackerError::Io {
+        path: sprite_info_path.to_path_buf(),
+        source,
+    })
+}
+
+/// Pack several collections into atlases in parallel and save each of them.
+/// # Arguments
+/// * `collections` - The collections to pack
+/// * `sprites_path` - The path to the sprites folder
+/// * `output_dir` - The folder to save the atlases to, or `None` to save each into a `Packed` folder next to the collection's atlas
+/// * `layout` - How to lay the sprites out in each atlas
+/// * `backup` - The backup to copy existing files into before they are overwritten, if any
+/// * `cancel` - The token to stop packing early with, if any; atlases that were already saved are kept
+/// * `on_progress` - Called with the fraction of all sprites packed so far
+/// # Returns
+/// * `Vec<Result<PathBuf>>` Where each atlas was saved, in the same order as `collections`
+pub fn pack_collections(
+    collections: &[Collection],
+    sprites_path: &Path,
+    output_dir: Option<&Path>,
+    layout: Layout,
+    backup: Option<&Backup>,
+    cancel: Option<&CancelToken>,
+    on_progress: impl Fn(f32) + Sync,
+) -> Vec<Result<PathBuf>> {
+    let total_sprites = collections
+        .iter()
+        .map(|collection| collection.sprites.len())
+        .sum::<usize>()
+        .max(1);
+    let packed_fractions = Mutex::new(vec![0_f32; collections.len()]);
+    collections
+        .par_iter()
+        .enumerate()
+        .map(|(index, collection)| {
+            let atlas_path = atlas_output_path(collection, output_dir);
+            pack_and_save(
+                collection,
+                sprites_path,
+                &atlas_path,
+                layout,
+                backup,
+                cancel,
+                |progress| {
+                    let mut fractions = packed_fractions.lock().unwrap();
+                    fractions[index] = progress;
+                    let packed_sprites = fractions
+                        .iter()
+                        .zip(collections)
+                        .map(|(fraction, collection)| fraction * collection.sprites.len() as f32)
+                        .sum::<f32>();
+                    on_progress(packed_sprites / total_sprites as f32);
+                },
+            )?;
+            Ok(atlas_path)
+        })
+        .collect()
+}
+
+/// Get the path that the atlas of a collection is saved to.
+/// # Arguments
+/// * `collection` - The collection that was packed
+/// * `output_dir` - The folder to save the atlas to, or `None` to save it into a `Packed` folder next to the collection's atlas
+/// # Returns
+/// * `PathBuf` The path to save the atlas to, which is never the collection's original atlas by default
+pub fn atlas_output_path(collection: &Collection, output_dir: Option<&Path>) -> PathBuf {
+    let file_name = format!("{}.png", collection.name);
+    match output_dir {
+        Some(output_dir) => output_dir.join(file_name),
+        None => collection
+            .path
+            .parent()
+            .unwrap_or_else(|| Path::new(""))
+            .join(PACKED_DIR_NAME)
+            .join(file_name),
+    }
+}
+
+/// Get the path that the sprite info of a repacked atlas is saved to.
+/// # Arguments
+/// * `atlas_path` - The path the atlas is saved to
+/// # Returns
+/// * `PathBuf` A JSON file next to the atlas with the same name
+pub fn sprite_info_path(atlas_path: &Path) -> PathBuf {
+    atlas_path.with_extension("json")
+}
+
+/// Get every file that packing a collection into an atlas may write, besides backups.
+/// # Arguments
+/// * `atlas_path` - The path the atlas is saved to
+/// # Returns
+/// * `Vec<PathBuf>` The atlas, the temporary file it is written to first and its sprite info
+pub fn written_paths(atlas_path: &Path) -> Vec<PathBuf> {
+    vec![
+        atlas_path.to_path_buf(),
+        temp_path(atlas_path),
+        sprite_info_path(atlas_path),
+    ]
+}
+
+/// Save a generated atlas, creating the folder it is saved to if needed.
+/// The atlas is written to a temporary file first, so an existing atlas is never left half-written.
+/// # Arguments
+/// * `atlas` - The atlas to save
+/// * `atlas_path` - The path to save the atlas to
+/// * `backup` - The backup to copy the existing atlas into before it is overwritten, if any
+/// # Returns
+/// * `Result<()>` Whether the atlas was saved
+pub fn save_atlas(atlas: &DynamicImage, atlas_path: &Path, backup: Option<&Backup>) -> Result<()> {
+    if let Some(backup) = backup {
+        backup.snapshot(atlas_path)?;
+    }
+    save_image(atlas, atlas_path)
+}
+
+/// Get the position in an atlas that a pixel of a frame is packed to.
+/// # Arguments
+/// * `sprite` - The sprite the frame belongs to
+/// * `i` - The x coordinate of the pixel in the frame, from the left
+/// * `j` - The y coordinate of the pixel in the frame, from the bottom
+/// * `atlas_height` - The height of the atlas
+/// # Returns
+/// * `(i32, i32)` The x and y coordinates in the atlas, from the top left
+pub fn atlas_position(sprite: &Sprite, i: i32, j: i32, atlas_height: i32) -> (i32, i32) {
+    if sprite.flipped {
+        (
+            sprite.x + j - sprite.yr,
+            atlas_height - (sprite.y + i) - 1 + sprite.xr,
+        )
+    } else {
+        (
+            sprite.x + i - sprite.xr,
+            atlas_height - (sprite.y + j) - 1 + sprite.yr,
+        )
+    }
+}
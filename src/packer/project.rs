@@ -0,0 +1,267 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::{
+    error::{Result, SpritePackerError},
+    packer::{pack::Layout, resolve_path},
+    tk2d::sprite::Sprite,
+};
+
+/// The name of the file in the sprites folder that the options of a dump are stored in.
+pub const PROJECT_FILE_NAME: &str = "spritepacker.json";
+
+/// The duplicate that was kept the last time a changed sprite was resolved.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Resolution {
+    /// The name of the collection the sprite belongs to
+    #[serde(rename = "collectionName")]
+    pub collection_name: String,
+    /// The ID shared by the sprite and its duplicates
+    pub id: u32,
+    /// The path of the duplicate that was kept, as stored in the dump
+    pub path: String,
+    /// When the kept duplicate was last modified at the time it was kept, since the Unix epoch
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified: Option<Duration>,
+    /// The size in bytes of the kept duplicate at the time it was kept
+    #[serde(default)]
+    pub size: u64,
+}
+
+/// The options of a single dump, stored with it so that they apply wherever it is packed from.
+/// Options that are not set fall back to the app's settings or the command line.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Project {
+    /// The folder to save generated atlases to, relative to the sprites folder or absolute.
+    /// An empty path is treated as no output directory, and is never saved
+    #[serde(
+        default,
+        rename = "outputDir",
+        deserialize_with = "deserialize_output_dir",
+        skip_serializing_if = "is_unset"
+    )]
+    pub output_dir: Option<PathBuf>,
+    /// How sprites are laid out in generated atlases
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub layout: Option<Layout>,
+    /// The names of collections that are left out when checking, resolving or packing every collection
+    #[serde(default, rename = "ignoredCollections")]
+    pub ignored_collections: Vec<String>,
+    /// The duplicate kept for each sprite that was resolved, kept again if the strategy cannot decide
+    /// and the duplicate has not changed since
+    #[serde(default)]
+    pub resolutions: Vec<Resolution>,
+}
+
+impl Project {
+    /// Open the project file of a dump.
+    /// A missing project file is treated as a project with no options set.
+    /// # Arguments
+    /// * `sprites_path` - The path to the sprites folder
+    /// # Returns
+    /// * `Result<Project>` The project, or an error if the project file could not be read
+    pub fn open(sprites_path: &Path) -> Result<Self> {
+        let path = sprites_path.join(PROJECT_FILE_NAME);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let text = fs::read_to_string(&path).map_err(|source| SpritePackerError::Io {
+            path: path.clone(),
+            source,
+        })?;
+        serde_json::from_str(&text).map_err(|source| SpritePackerError::Json { path, source })
+    }
+
+    /// Save the project file of a dump.
+    /// # Arguments
+    /// * `sprites_path` - The path to the sprites folder
+    /// # Returns
+    /// * `Result<()>` Whether the project file was saved
+    pub fn save(&self, sprites_path: &Path) -> Result<()> {
+        let path = sprites_path.join(PROJECT_FILE_NAME);
+        let text =
+            serde_json::to_string_pretty(self).map_err(|source| SpritePackerError::Json {
+                path: path.clone(),
+                source,
+            })?;
+        fs::write(&path, text).map_err(|source| SpritePackerError::Io { path, source })
+    }
+
+    /// Get the folder to save generated atlases to.
+    /// # Arguments
+    /// * `sprites_path` - The path to the sprites folder
+    /// # Returns
+    /// * `Option<PathBuf>` The output directory, or `None` if the project does not set one
+    pub fn output_dir(&self, sprites_path: &Path) -> Option<PathBuf> {
+        if is_unset(&self.output_dir) {
+            return None;
+        }
+        self.output_dir
+            .as_ref()
+            .map(|output_dir| sprites_path.join(output_dir))
+    }
+
+    /// Check whether a collection is left out when checking, resolving or packing every collection.
+    /// # Arguments
+    /// * `collection_name` - The name of the collection
+    /// # Returns
+    /// * `bool` Whether the collection is ignored
+    pub fn is_ignored(&self, collection_name: &str) -> bool {
+        self.ignored_collections
+            .iter()
+            .any(|name| name == collection_name)
+    }
+
+    /// Get the duplicate that was kept the last time a group of duplicates was resolved.
+    /// A duplicate that was modified since it was kept is not returned, as keeping it again could overwrite newer edits.
+    /// # Arguments
+    /// * `sprites_path` - The path to the sprites folder
+    /// * `duplicates` - The sprites sharing an ID in a collection
+    /// # Returns
+    /// * `Option<&Sprite>` The duplicate that was kept, if it is still one of them and is unchanged since
+    pub fn resolved_source<'a>(
+        &self,
+        sprites_path: &Path,
+        duplicates: &'a [Sprite],
+    ) -> Option<&'a Sprite> {
+        let first = duplicates.first()?;
+        let resolution = self.resolutions.iter().find(|resolution| {
+            resolution.collection_name == first.collection_name && resolution.id == first.id
+        })?;
+        let (modified, size) = file_stamp(&resolve_path(sprites_path, &resolution.path))?;
+        if resolution.modified != Some(modified) || resolution.size != size {
+            return None;
+        }
+        duplicates
+            .iter()
+            .find(|sprite| sprite.path == resolution.path)
+    }
+
+    /// Check whether a sprite is the duplicate that was kept the last time its group of duplicates was resolved.
+    /// # Arguments
+    /// * `sprite` - The sprite
+    /// # Returns
+    /// * `bool` Whether the sprite was kept, whether or not it has changed since
+    pub fn is_resolution(&self, sprite: &Sprite) -> bool {
+        self.resolutions.iter().any(|resolution| {
+            resolution.collection_name == sprite.collection_name
+                && resolution.id == sprite.id
+                && resolution.path == sprite.path
+        })
+    }
+
+    /// Remember which duplicate of a sprite was kept, replacing what was remembered before.
+    /// # Arguments
+    /// * `sprites_path` - The path to the sprites folder
+    /// * `source_sprite` - The duplicate that the others were replaced with
+    pub fn add_resolution(&mut self, sprites_path: &Path, source_sprite: &Sprite) {
+        self.resolutions.retain(|resolution| {
+            resolution.collection_name != source_sprite.collection_name
+                || resolution.id != source_sprite.id
+        });
+        let file_stamp = file_stamp(&resolve_path(sprites_path, &source_sprite.path));
+        self.resolutions.push(Resolution {
+            collection_name: source_sprite.collection_name.clone(),
+            id: source_sprite.id,
+            path: source_sprite.path.clone(),
+            modified: file_stamp.map(|(modified, _)| modified),
+            size: file_stamp.map_or(0, |(_, size)| size),
+        });
+    }
+}
+
+/// Get when a file was last modified and how large it is.
+/// # Arguments
+/// * `path` - The path to the file
+/// # Returns
+/// * `Option<(Duration, u64)>` The modification time since the Unix epoch and the size in bytes, if they could be read
+fn file_stamp(path: &Path) -> Option<(Duration, u64)> {
+    let metadata = fs::metadata(path).ok()?;
+    let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+    Some((modified, metadata.len()))
+}
+
+/// Check whether an output directory is unset or empty.
+/// # Arguments
+/// * `output_dir` - The output directory
+/// # Returns
+/// * `bool` Whether there is no output directory to save to
+fn is_unset(output_dir: &Option<PathBuf>) -> bool {
+    output_dir
+        .as_ref()
+        .map_or(true, |output_dir| output_dir.as_os_str().is_empty())
+}
+
+/// Read an output directory from a project file, treating an empty path as none.
+/// # Arguments
+/// * `deserializer` - The deserializer to read from
+/// # Returns
+/// * `Result<Option<PathBuf>, D::Error>` The output directory, if one is set
+fn deserialize_output_dir<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Option<PathBuf>, D::Error> {
+    let output_dir = Option::<PathBuf>::deserialize(deserializer)?;
+    Ok(output_dir.filter(|output_dir| !output_dir.as_os_str().is_empty()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Create an empty sprites folder in the temporary directory for a test.
+    fn test_folder(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "spritepacker-project-{name}-{}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        path
+    }
+
+    #[test]
+    fn project_survives_save_and_open() {
+        let folder = test_folder("round-trip");
+        let project = Project {
+            output_dir: Some(PathBuf::from("Packed")),
+            layout: Some(Layout::Repack {
+                max_size: 2048,
+                padding: 2,
+                retrim: true,
+            }),
+            ignored_collections: vec!["Knight".to_string()],
+            resolutions: vec![Resolution {
+                collection_name: "Knight".to_string(),
+                id: 3,
+                path: "Knight/Idle-0-3.png".to_string(),
+                modified: Some(Duration::from_millis(1706189820123)),
+                size: 512,
+            }],
+        };
+        project.save(&folder).unwrap();
+
+        assert_eq!(Project::open(&folder).unwrap(), project);
+        fs::remove_dir_all(folder).unwrap();
+    }
+
+    #[test]
+    fn missing_options_fall_back_to_defaults() {
+        let folder = test_folder("defaults");
+        assert_eq!(Project::open(&folder).unwrap(), Project::default());
+
+        fs::write(folder.join(PROJECT_FILE_NAME), r#"{"outputDir": ""}"#).unwrap();
+        let project = Project::open(&folder).unwrap();
+        assert_eq!(project, Project::default());
+        assert_eq!(project.output_dir(&folder), None);
+
+        Project::default().save(&folder).unwrap();
+        let text = fs::read_to_string(folder.join(PROJECT_FILE_NAME)).unwrap();
+        assert!(!text.contains("outputDir") && !text.contains("layout"));
+        fs::remove_dir_all(folder).unwrap();
+    }
+}
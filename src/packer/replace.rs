@@ -0,0 +1,180 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use image::{imageops, GenericImageView};
+use log::info;
+
+use crate::{
+    error::{Result, SpritePackerError},
+    packer::{backup::Backup, history::FileChange},
+    tk2d::{
+        cln::Collection,
+        sprite::{Sprite, SpriteImage},
+    },
+};
+
+use super::{resolve_path, save_image, temp_path};
+
+/// A frame that would be overwritten by replacing the duplicates of a sprite.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FileReport {
+    /// The path to the frame image
+    pub path: PathBuf,
+    /// The number of pixels that would change
+    pub changed_pixels: usize,
+}
+
+/// Replace all duplicates of a sprite in a collection with that sprite.
+/// Frames that are already identical to the sprite are left untouched.
+/// # Arguments
+/// * `sprites_path` - The path to the sprites folder
+/// * `collection` - The collection the sprite belongs to
+/// * `source_sprite` - The sprite to replace duplicates with
+/// * `backup` - The backup to copy each frame into before it is overwritten, if any
+/// * `changes` - Where the contents of each frame, before and after it was replaced, are added as soon as it is written,
+///   so that the frames replaced before an error can still be undone
+/// # Returns
+/// * `Result<()>` Whether every duplicate was replaced
+pub fn replace_duplicate_sprites(
+    sprites_path: &Path,
+    collection: &Collection,
+    source_sprite: &Sprite,
+    backup: Option<&Backup>,
+    changes: &mut Vec<FileChange>,
+) -> Result<()> {
+    for (report, sprite_image) in replaced_frames(sprites_path, collection, source_sprite)? {
+        let sprite_path = report.path;
+        if let Some(backup) = backup {
+            backup.snapshot(&sprite_path)?;
+        }
+        let before = fs::read(&sprite_path).map_err(|source| SpritePackerError::Io {
+            path: sprite_path.clone(),
+            source,
+        })?;
+        // The frame is written to a temporary file first, so a failed save leaves it as it was
+        // and only a change that went through is recorded
+        let saved = save_image(&sprite_image.image, &sprite_path);
+        let after = fs::read(&sprite_path).unwrap_or_default();
+        if after != before {
+            changes.push(FileChange {
+                path: sprite_path.clone(),
+                before,
+                after,
+            });
+        }
+        saved?;
+        info!(
+            "Replaced sprite at path {:?} with sprite at path {:?}",
+            sprite_path.display(),
+            source_sprite.path
+        );
+    }
+
+    Ok(())
+}
+
+/// Find the frames that replacing all duplicates of a sprite would overwrite, without writing anything.
+/// # Arguments
+/// * `sprites_path` - The path to the sprites folder
+/// * `collection` - The collection the sprite belongs to
+/// * `source_sprite` - The sprite to replace duplicates with
+/// # Returns
+/// * `Result<Vec<FileReport>>` The frames that would be overwritten and how many of their pixels would change
+pub fn dry_run_replace(
+    sprites_path: &Path,
+    collection: &Collection,
+    source_sprite: &Sprite,
+) -> Result<Vec<FileReport>> {
+    Ok(replaced_frames(sprites_path, collection, source_sprite)?
+        .into_iter()
+        .map(|(report, _)| report)
+        .collect())
+}
+
+/// Get every file that replacing all duplicates of a sprite may write, besides backups.
+/// # Arguments
+/// * `sprites_path` - The path to the sprites folder
+/// * `collection` - The collection the sprite belongs to
+/// * `source_sprite` - The sprite to replace duplicates with
+/// # Returns
+/// * `Vec<PathBuf>` The frame of every duplicate and the temporary file it is written to first
+pub fn written_paths(
+    sprites_path: &Path,
+    collection: &Collection,
+    source_sprite: &Sprite,
+) -> Vec<PathBuf> {
+    collection
+        .sprites
+        .iter()
+        .filter(|sprite| sprite.id == source_sprite.id)
+        .flat_map(|sprite| {
+            let sprite_path = resolve_path(sprites_path, &sprite.path);
+            [temp_path(&sprite_path), sprite_path]
+        })
+        .collect()
+}
+
+/// Replace all duplicates of a sprite with that sprite in memory.
+/// # Arguments
+/// * `sprites_path` - The path to the sprites folder
+/// * `collection` - The collection the sprite belongs to
+/// * `source_sprite` - The sprite to replace duplicates with
+/// # Returns
+/// * `Result<Vec<(FileReport, SpriteImage)>>` Each frame that changed, along with its replaced image
+fn replaced_frames(
+    sprites_path: &Path,
+    collection: &Collection,
+    source_sprite: &Sprite,
+) -> Result<Vec<(FileReport, SpriteImage)>> {
+    let source_image = SpriteImage::open(sprites_path, source_sprite)?;
+
+    let mut replaced_frames = vec![];
+    for sprite in &collection.sprites {
+        if sprite.id != source_sprite.id {
+            continue;
+        }
+
+        let mut sprite_image = SpriteImage::open(sprites_path, sprite)?;
+        if sprite_image.sprite.width != source_sprite.width
+            || sprite_image.sprite.height != source_sprite.height
+        {
+            return Err(SpritePackerError::SizeMismatch(
+                sprite.path.clone(),
+                source_sprite.path.clone(),
+            ));
+        }
+
+        let original_image = sprite_image.image.clone();
+        replace_sprite(&source_image, &mut sprite_image);
+        let changed_pixels = original_image
+            .pixels()
+            .zip(sprite_image.image.pixels())
+            .filter(|((_, _, pixel1), (_, _, pixel2))| pixel1 != pixel2)
+            .count();
+        if changed_pixels == 0 {
+            continue;
+        }
+
+        let report = FileReport {
+            path: resolve_path(sprites_path, &sprite.path),
+            changed_pixels,
+        };
+        replaced_frames.push((report, sprite_image));
+    }
+
+    Ok(replaced_frames)
+}
+
+/// Replace a sprite with another sprite.
+/// # Arguments
+/// * `source_image` - The sprite to replace with
+/// * `target_image` - The sprite to replace
+pub fn replace_sprite(source_image: &SpriteImage, target_image: &mut SpriteImage) {
+    let target_sprite = &target_image.sprite;
+    let x = target_sprite.xr as i64;
+    let y =
+        target_image.image.height() as i64 - target_sprite.yr as i64 - target_sprite.height as i64;
+    imageops::replace(&mut target_image.image, &*source_image.trim(), x, y);
+}
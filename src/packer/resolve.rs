@@ -0,0 +1,213 @@
+use std::{collections::HashMap, fs, path::Path, time::SystemTime};
+
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{Result, SpritePackerError},
+    packer::{
+        cache::{frame_entry, CachedAtlas, HashCache},
+        cancel::{check_cancelled, CancelToken},
+        check::{group_identical_frames, is_edited},
+        project::Project,
+        resolve_path,
+    },
+    tk2d::{cln::Collection, sprite::Sprite},
+};
+
+/// How to choose which duplicate of a changed sprite to replace the others with.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub enum Strategy {
+    /// The duplicate whose frame image was modified most recently
+    #[default]
+    Newest,
+    /// The duplicate that differs from the collection's atlas, as long as every such duplicate is identical
+    DiffersFromAtlas,
+    /// The duplicate that is identical to more of the others than any other duplicate
+    Majority,
+}
+
+/// A group of duplicates that are not all identical.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Conflict {
+    /// Every sprite in the collection with the same ID
+    pub duplicates: Vec<Sprite>,
+    /// The duplicate chosen to replace the others with, or `None` if the strategy could not choose one
+    pub source: Option<Sprite>,
+}
+
+/// Choose which duplicate to replace the others with for each group of changed sprites.
+/// # Arguments
+/// * `sprites_path` - The path to the sprites folder
+/// * `collections` - The collections the changed sprites belong to
+/// * `changed_sprites` - The sprites that differ from one of their duplicates, as found by `check`
+/// * `strategy` - How to choose the duplicate
+/// * `cache` - The cache to look up the hashes and atlas comparisons of unchanged frames in, if any
+/// * `project` - The project whose remembered choices settle groups the strategy cannot decide on, if any
+/// * `cancel` - The token to stop choosing early with, if any
+/// # Returns
+/// * `Result<Vec<Conflict>>` Each group of duplicates and the one chosen from it, in the order they were found
+pub fn choose_sources(
+    sprites_path: &Path,
+    collections: &[Collection],
+    changed_sprites: &[Sprite],
+    strategy: Strategy,
+    cache: Option<&HashCache>,
+    project: Option<&Project>,
+    cancel: Option<&CancelToken>,
+) -> Result<Vec<Conflict>> {
+    let mut group_keys = vec![];
+    for sprite in changed_sprites {
+        let key = (sprite.collection_name.clone(), sprite.id);
+        if !group_keys.contains(&key) {
+            group_keys.push(key);
+        }
+    }
+
+    // Each atlas is only decoded once a frame that is not cached has to be compared with it
+    let mut atlases = HashMap::<String, CachedAtlas>::new();
+    if strategy == Strategy::DiffersFromAtlas {
+        for (collection_name, _) in &group_keys {
+            if !atlases.contains_key(collection_name) {
+                let collection = find_collection(collections, collection_name)?;
+                atlases.insert(collection_name.clone(), CachedAtlas::new(collection));
+            }
+        }
+    }
+
+    group_keys
+        .into_par_iter()
+        .map(|(collection_name, id)| {
+            check_cancelled(cancel)?;
+            let collection = find_collection(collections, &collection_name)?;
+            let duplicates = collection
+                .sprites
+                .iter()
+                .filter(|sprite| sprite.id == id)
+                .cloned()
+                .collect::<Vec<_>>();
+            let source = match strategy {
+                Strategy::Newest => choose_newest(sprites_path, &duplicates)?,
+                Strategy::DiffersFromAtlas => {
+                    choose_differing(sprites_path, &duplicates, cache, &atlases[&collection_name])?
+                }
+                Strategy::Majority => choose_majority(sprites_path, &duplicates, cache)?,
+            };
+            // The duplicate kept last time only settles what the strategy cannot
+            let source = source.or_else(|| {
+                project
+                    .and_then(|project| project.resolved_source(sprites_path, &duplicates))
+                    .cloned()
+            });
+            Ok(Conflict { duplicates, source })
+        })
+        .collect()
+}
+
+/// Find a collection by its name.
+/// # Arguments
+/// * `collections` - The collections to search
+/// * `collection_name` - The name of the collection
+/// # Returns
+/// * `Result<&Collection>` The collection with the given name
+fn find_collection<'a>(
+    collections: &'a [Collection],
+    collection_name: &str,
+) -> Result<&'a Collection> {
+    collections
+        .iter()
+        .find(|collection| collection.name == collection_name)
+        .ok_or_else(|| SpritePackerError::CollectionNotFound(collection_name.to_string()))
+}
+
+/// Choose the duplicate whose frame image was modified most recently.
+/// # Arguments
+/// * `sprites_path` - The path to the sprites folder
+/// * `duplicates` - The duplicates to choose from
+/// # Returns
+/// * `Result<Option<Sprite>>` The newest duplicate, or `None` if several were modified at the same time
+fn choose_newest(sprites_path: &Path, duplicates: &[Sprite]) -> Result<Option<Sprite>> {
+    let mut modified_times = vec![];
+    for sprite in duplicates {
+        let sprite_path = resolve_path(sprites_path, &sprite.path);
+        let modified_time = fs::metadata(&sprite_path)
+            .and_then(|metadata| metadata.modified())
+            .map_err(|source| SpritePackerError::Io {
+                path: sprite_path.clone(),
+                source,
+            })?;
+        modified_times.push((modified_time, sprite));
+    }
+
+    let newest_time = modified_times
+        .iter()
+        .map(|(modified_time, _)| *modified_time)
+        .max()
+        .unwrap_or(SystemTime::UNIX_EPOCH);
+    let newest = modified_times
+        .iter()
+        .filter(|(modified_time, _)| *modified_time == newest_time)
+        .collect::<Vec<_>>();
+    Ok(match newest.as_slice() {
+        [(_, sprite)] => Some((*sprite).clone()),
+        _ => None,
+    })
+}
+
+/// Choose the duplicate that differs from the region of the atlas it was dumped from.
+/// # Arguments
+/// * `sprites_path` - The path to the sprites folder
+/// * `duplicates` - The duplicates to choose from
+/// * `cache` - The cache to look up how unchanged frames compared with the atlas in, if any
+/// * `atlas` - The atlas of the collection
+/// # Returns
+/// * `Result<Option<Sprite>>` The first differing duplicate, or `None` if none differ or they differ in different ways
+fn choose_differing(
+    sprites_path: &Path,
+    duplicates: &[Sprite],
+    cache: Option<&HashCache>,
+    atlas: &CachedAtlas,
+) -> Result<Option<Sprite>> {
+    let mut differing_sprites = vec![];
+    for sprite in duplicates {
+        let entry = frame_entry(sprites_path, sprite, cache, Some(atlas))?;
+        if is_edited(&entry) {
+            differing_sprites.push((sprite, entry.hash));
+        }
+    }
+
+    let groups = group_identical_frames(sprites_path, &differing_sprites)?;
+    Ok(match groups.as_slice() {
+        [group] => group.first().map(|sprite| (*sprite).clone()),
+        _ => None,
+    })
+}
+
+/// Choose the duplicate that is identical to the most other duplicates.
+/// # Arguments
+/// * `sprites_path` - The path to the sprites folder
+/// * `duplicates` - The duplicates to choose from
+/// * `cache` - The cache to look up the hashes of unchanged frames in, if any
+/// # Returns
+/// * `Result<Option<Sprite>>` The first duplicate of the largest group of identical ones, or `None` if there is a tie
+fn choose_majority(
+    sprites_path: &Path,
+    duplicates: &[Sprite],
+    cache: Option<&HashCache>,
+) -> Result<Option<Sprite>> {
+    let hashed_sprites = duplicates
+        .iter()
+        .map(|sprite| Ok((sprite, frame_entry(sprites_path, sprite, cache, None)?.hash)))
+        .collect::<Result<Vec<_>>>()?;
+    let groups = group_identical_frames(sprites_path, &hashed_sprites)?;
+
+    let largest_count = groups.iter().map(Vec::len).max().unwrap_or(0);
+    let largest = groups
+        .iter()
+        .filter(|group| group.len() == largest_count)
+        .collect::<Vec<_>>();
+    Ok(match largest.as_slice() {
+        [group] => group.first().map(|sprite| (*sprite).clone()),
+        _ => None,
+    })
+}
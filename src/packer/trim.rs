@@ -0,0 +1,137 @@
+use std::{collections::HashMap, path::Path};
+
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::Result,
+    packer::cache::{frame_entry, CacheEntry, HashCache},
+    tk2d::{
+        cln::Collection,
+        sprite::{Sprite, SpriteImage},
+    },
+};
+
+/// A frame with opaque pixels outside the trim rect of its sprite.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize, Serialize)]
+pub struct ExceedingFrame {
+    /// The sprite as dumped
+    pub sprite: Sprite,
+    /// The sprite with its trim rect fitted to the opaque pixels of its frame
+    pub trimmed_sprite: Sprite,
+    /// The width of the frame image
+    pub frame_width: u32,
+    /// The height of the frame image
+    pub frame_height: u32,
+}
+
+/// Find the frames of a collection whose opaque pixels would be clipped when packed.
+/// # Arguments
+/// * `sprites_path` - The path to the sprites folder
+/// * `collection` - The collection to check
+/// * `cache` - The cache to look up the opaque bounds of unchanged frames in, if any
+/// # Returns
+/// * `Result<Vec<ExceedingFrame>>` The frames with pixels outside their trim rect
+pub fn find_exceeding_frames(
+    sprites_path: &Path,
+    collection: &Collection,
+    cache: Option<&HashCache>,
+) -> Result<Vec<ExceedingFrame>> {
+    let exceeding_frames = collection
+        .sprites
+        .par_iter()
+        .map(|sprite| {
+            let entry = frame_entry(sprites_path, sprite, cache, None)?;
+            Ok(exceeding_frame(sprite, &entry))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(exceeding_frames.into_iter().flatten().collect())
+}
+
+/// Check whether what is known about a frame shows opaque pixels outside the trim rect of its sprite.
+/// # Arguments
+/// * `sprite` - The sprite
+/// * `entry` - What is known about the sprite's frame
+/// # Returns
+/// * `Option<ExceedingFrame>` The frame, if packing it would clip any of its pixels
+pub fn exceeding_frame(sprite: &Sprite, entry: &CacheEntry) -> Option<ExceedingFrame> {
+    let [xr, yr, width, height] = entry.opaque_trim?;
+    // A fully transparent frame has nothing to clip
+    if width == 0 || height == 0 {
+        return None;
+    }
+    let exceeds_trim = xr < sprite.xr
+        || yr < sprite.yr
+        || xr + width > sprite.xr + sprite.width
+        || yr + height > sprite.yr + sprite.height;
+    exceeds_trim.then(|| ExceedingFrame {
+        sprite: sprite.clone(),
+        trimmed_sprite: Sprite {
+            xr,
+            yr,
+            width,
+            height,
+            ..sprite.clone()
+        },
+        frame_width: entry.width,
+        frame_height: entry.height,
+    })
+}
+
+/// Fit the trim rect of every sprite in a collection to the opaque pixels of its frames.
+/// Duplicates of a sprite are given the same trim rect, which covers the opaque pixels of all of them.
+/// # Arguments
+/// * `sprites_path` - The path to the sprites folder
+/// * `collection` - The collection to trim
+/// # Returns
+/// * `Result<Collection>` The collection with the new trim rects
+pub fn retrim_collection(sprites_path: &Path, collection: &Collection) -> Result<Collection> {
+    let trimmed_sprites = collection
+        .sprites
+        .par_iter()
+        .map(|sprite| {
+            let sprite_image = SpriteImage::open(sprites_path, sprite)?;
+            Ok(sprite_image.opaque_trim())
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut trim_rects = HashMap::<u32, (i32, i32, i32, i32)>::new();
+    for trimmed_sprite in trimmed_sprites.iter().flatten() {
+        let x_min = trimmed_sprite.xr;
+        let y_min = trimmed_sprite.yr;
+        let x_max = trimmed_sprite.xr + trimmed_sprite.width;
+        let y_max = trimmed_sprite.yr + trimmed_sprite.height;
+        trim_rects
+            .entry(trimmed_sprite.id)
+            .and_modify(|rect| {
+                *rect = (
+                    rect.0.min(x_min),
+                    rect.1.min(y_min),
+                    rect.2.max(x_max),
+                    rect.3.max(y_max),
+                )
+            })
+            .or_insert((x_min, y_min, x_max, y_max));
+    }
+
+    let sprites = collection
+        .sprites
+        .iter()
+        .map(|sprite| match trim_rects.get(&sprite.id) {
+            Some(&(x_min, y_min, x_max, y_max)) => Sprite {
+                xr: x_min,
+                yr: y_min,
+                width: x_max - x_min,
+                height: y_max - y_min,
+                ..sprite.clone()
+            },
+            None => sprite.clone(),
+        })
+        .collect();
+
+    Ok(Collection {
+        sprites,
+        ..collection.clone()
+    })
+}
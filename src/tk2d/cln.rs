@@ -2,7 +2,7 @@ use crate::tk2d::sprite::Sprite;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
-#[derive(Clone, Default, Deserialize, Serialize)]
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
 pub struct Collection {
     pub name: String,
     pub path: PathBuf,
@@ -38,20 +38,49 @@ pub struct SpriteInfo {
 }
 
 impl SpriteInfo {
+    /// Create sprite info for a list of sprites, e.g. the sprites of a packed collection.
+    /// # Arguments
+    /// * `sprites` - The sprites
+    /// # Returns
+    /// * `SpriteInfo` The sprite info, in the same order as `sprites`
+    pub fn from_sprites(sprites: &[Sprite]) -> Self {
+        Self {
+            id: sprites.iter().map(|sprite| sprite.id).collect(),
+            x: sprites.iter().map(|sprite| sprite.x).collect(),
+            y: sprites.iter().map(|sprite| sprite.y).collect(),
+            xr: sprites.iter().map(|sprite| sprite.xr).collect(),
+            yr: sprites.iter().map(|sprite| sprite.yr).collect(),
+            width: sprites.iter().map(|sprite| sprite.width).collect(),
+            height: sprites.iter().map(|sprite| sprite.height).collect(),
+            collection_name: sprites
+                .iter()
+                .map(|sprite| sprite.collection_name.clone())
+                .collect(),
+            path: sprites.iter().map(|sprite| sprite.path.clone()).collect(),
+            flipped: sprites.iter().map(|sprite| sprite.flipped).collect(),
+        }
+    }
+
+    /// Get the sprite at an index.
+    /// # Arguments
+    /// * `index` - The index of the sprite
+    /// # Returns
+    /// * `Option<Sprite>` The sprite, or `None` if any of the arrays is too short
     pub fn at(&self, index: usize) -> Option<Sprite> {
-        let sprite_name = PathBuf::from(self.path[index].clone()).file_name()?.to_str()?.to_string();
+        let path = self.path.get(index)?;
+        let sprite_name = PathBuf::from(path).file_name()?.to_str()?.to_string();
         Some(Sprite {
-            id: self.id[index],
-            x: self.x[index],
-            y: self.y[index],
-            xr: self.xr[index],
-            yr: self.yr[index],
-            width: self.width[index],
-            height: self.height[index],
-            collection_name: self.collection_name[index].clone(),
+            id: *self.id.get(index)?,
+            x: *self.x.get(index)?,
+            y: *self.y.get(index)?,
+            xr: *self.xr.get(index)?,
+            yr: *self.yr.get(index)?,
+            width: *self.width.get(index)?,
+            height: *self.height.get(index)?,
+            collection_name: self.collection_name.get(index)?.clone(),
             name: sprite_name,
-            path: self.path[index].clone(),
-            flipped: self.flipped[index],
+            path: path.clone(),
+            flipped: *self.flipped.get(index)?,
         })
     }
-}
\ No newline at end of file
+}
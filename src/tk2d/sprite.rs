@@ -1,10 +1,13 @@
-use std::{ops::ControlFlow, sync::Mutex};
+use std::path::Path;
 
 use image::{DynamicImage, GenericImageView, SubImage};
-use log::info;
-use rayon::prelude::{IntoParallelIterator, ParallelIterator};
 use serde::{Deserialize, Serialize};
 
+use crate::{
+    error::{Result, SpritePackerError},
+    packer::{pack::atlas_position, resolve_path},
+};
+
 #[derive(Clone, Debug, Default, Eq, Hash, PartialEq, Serialize, Deserialize)]
 pub struct Sprite {
     pub id: u32,
@@ -27,43 +30,48 @@ pub struct SpriteImage {
 }
 
 impl SpriteImage {
-    pub fn equals(&self, other: &Self) -> bool {
-        if self.sprite.collection_name != other.sprite.collection_name {
-            info!(
-                "Collection names don't match: {} vs {}",
-                self.sprite.collection_name, other.sprite.collection_name
-            );
-            return false;
-        }
-        let frame1 = self.trim();
-        let frame2 = other.trim();
-        if frame1.width() != frame2.width() || frame1.height() != frame2.height() {
-            info!(
-                "Dimensions don't match: {}x{} vs {}x{}",
-                frame1.width(),
-                frame1.height(),
-                frame2.width(),
-                frame2.height()
-            );
-            return false;
+    /// Open the frame image of a sprite.
+    /// # Arguments
+    /// * `sprites_path` - The path to the sprites folder
+    /// * `sprite` - The sprite to open the frame image of
+    /// # Returns
+    /// * `Result<SpriteImage>` The sprite and its frame image
+    pub fn open(sprites_path: &Path, sprite: &Sprite) -> Result<Self> {
+        let path = resolve_path(sprites_path, &sprite.path);
+        let image = match image::open(&path) {
+            Ok(image) => image,
+            Err(source) => return Err(SpritePackerError::OpenImage { path, source }),
+        };
+
+        let sprite_image = Self {
+            sprite: sprite.clone(),
+            image,
+        };
+        if !sprite_image.trim_in_bounds() {
+            return Err(SpritePackerError::TrimOutOfBounds(sprite.path.clone()));
         }
 
-        let result = Mutex::new(true);
-        (0..frame1.width()).into_par_iter().try_for_each(|i| {
-            (0..frame2.height()).into_par_iter().try_for_each(|j| {
-                if frame1.get_pixel(i, j) != frame2.get_pixel(i, j) {
-                    *result.lock().expect("Failed to get lock on result") = false;
-                    return ControlFlow::Break(());
-                }
-                return ControlFlow::Continue(());
-            });
-            if !*result.lock().expect("Failed to get lock on result") {
-                return ControlFlow::Break(());
-            }
-            return ControlFlow::Continue(());
-        });
+        Ok(sprite_image)
+    }
+
+    /// Hash the dimensions and pixels of the trimmed frame.
+    /// Two frames with the same hash are identical, barring a collision, so duplicates can be grouped by it instead of being compared pair by pair.
+    /// The hash is stable across runs and platforms.
+    /// # Returns
+    /// * `u64` The 64-bit FNV-1a hash of the trimmed frame
+    pub fn trim_hash(&self) -> u64 {
+        const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+        const FNV_PRIME: u64 = 0x100000001b3;
 
-        return *result.lock().expect("Failed to get lock on result");
+        let frame = self.trim();
+        let mut hash = FNV_OFFSET_BASIS;
+        let dimensions = [frame.width().to_le_bytes(), frame.height().to_le_bytes()];
+        let pixels = frame.pixels().map(|(_, _, pixel)| pixel.0);
+        for byte in dimensions.into_iter().chain(pixels).flatten() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(FNV_PRIME);
+        }
+        hash
     }
 
     pub fn trim(&self) -> SubImage<&DynamicImage> {
@@ -76,4 +84,69 @@ impl SpriteImage {
             self.sprite.height as u32,
         );
     }
+
+    /// Check whether the trim rect of the sprite lies within its frame image.
+    /// # Returns
+    /// * `bool` Whether the trim rect can be safely viewed
+    pub fn trim_in_bounds(&self) -> bool {
+        let sprite = &self.sprite;
+        sprite.xr >= 0
+            && sprite.yr >= 0
+            && sprite.width >= 0
+            && sprite.height >= 0
+            && sprite.xr + sprite.width <= self.image.width() as i32
+            && sprite.yr + sprite.height <= self.image.height() as i32
+    }
+
+    /// Fit the trim rect of the sprite to the opaque pixels of its frame image.
+    /// # Returns
+    /// * `Option<Sprite>` The sprite with its new trim rect, or `None` if the frame is fully transparent
+    pub fn opaque_trim(&self) -> Option<Sprite> {
+        let mut bounds: Option<(u32, u32, u32, u32)> = None;
+        for (x, y, pixel) in self.image.pixels() {
+            if pixel[3] == 0 {
+                continue;
+            }
+            bounds = Some(match bounds {
+                Some((x_min, y_min, x_max, y_max)) => {
+                    (x_min.min(x), y_min.min(y), x_max.max(x), y_max.max(y))
+                }
+                None => (x, y, x, y),
+            });
+        }
+
+        let (x_min, y_min, x_max, y_max) = bounds?;
+        Some(Sprite {
+            xr: x_min as i32,
+            yr: (self.image.height() - y_max - 1) as i32,
+            width: (x_max - x_min + 1) as i32,
+            height: (y_max - y_min + 1) as i32,
+            ..self.sprite.clone()
+        })
+    }
+
+    /// Check whether the trimmed frame is identical to the region of an atlas that the sprite is packed into.
+    /// # Arguments
+    /// * `atlas` - The atlas the sprite was dumped from
+    /// # Returns
+    /// * `bool` Whether every pixel of the trimmed frame matches the atlas
+    pub fn matches_atlas(&self, atlas: &DynamicImage) -> bool {
+        let sprite = &self.sprite;
+        let atlas_width = atlas.width() as i32;
+        let atlas_height = atlas.height() as i32;
+        let frame_height = self.image.height() as i32;
+        (sprite.xr..sprite.xr + sprite.width).all(|i| {
+            (sprite.yr..sprite.yr + sprite.height).all(|j| {
+                let (x, y) = atlas_position(sprite, i, j, atlas_height);
+                x >= 0
+                    && x < atlas_width
+                    && y >= 0
+                    && y < atlas_height
+                    && atlas.get_pixel(x as u32, y as u32)
+                        == self
+                            .image
+                            .get_pixel(i as u32, (frame_height - j - 1) as u32)
+            })
+        })
+    }
 }